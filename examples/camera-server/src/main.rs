@@ -9,23 +9,20 @@ use nokhwa::utils::{
 };
 use nokhwa::{nokhwa_initialize, NokhwaError};
 use parking_lot::{Mutex, RwLock};
-use std::borrow::Cow;
 use std::sync::Arc;
 use std::time::SystemTime;
 use tokio::sync::{mpsc, oneshot, watch};
 use tokio::task;
 
-const ERR_EXPOSURE_FAILED_TO_STOP: ASCOMError = ASCOMError {
-    code: ASCOMErrorCode::new_for_driver(0),
-    message: Cow::Borrowed("Exposure failed to stop correctly"),
-};
+const ERR_EXPOSURE_FAILED_TO_STOP: ASCOMError = ASCOMError::new_const(
+    ASCOMErrorCode::new_for_driver(0),
+    "Exposure failed to stop correctly",
+);
 
-const ERR_EXPOSING_STATE_CHANGED_UNEXPECTEDLY: ASCOMError = ASCOMError {
-    code: ASCOMErrorCode::new_for_driver(1),
-    message: Cow::Borrowed(
-        "Internal error: exposing state changed unexpectedly during an active exposure",
-    ),
-};
+const ERR_EXPOSING_STATE_CHANGED_UNEXPECTEDLY: ASCOMError = ASCOMError::new_const(
+    ASCOMErrorCode::new_for_driver(1),
+    "Internal error: exposing state changed unexpectedly during an active exposure",
+);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 struct Point {
@@ -8,19 +8,24 @@ async fn main() -> eyre::Result<()> {
 
     println!("Searching...");
 
-    DiscoveryClient::new()
+    let discovery_client = DiscoveryClient::new();
+
+    discovery_client
         .bind()
         .await?
         .discover_addrs()
         .map(Ok)
-        .try_for_each_concurrent(None, |addr| async move {
-            let client = Client::new_from_addr(addr);
-            let server_info = client.get_server_info().await;
-            println!("Server info: {server_info:#?}");
-            let devices = client.get_devices().await?.collect::<Vec<_>>();
-            println!("Devices: {devices:#?}");
-            Ok::<_, eyre::Error>(())
-        })
+        .try_for_each_concurrent(
+            discovery_client.max_concurrent_requests,
+            |addr| async move {
+                let client = Client::new_from_addr(addr);
+                let server_info = client.get_server_info().await;
+                println!("Server info: {server_info:#?}");
+                let devices = client.get_devices().await?.collect::<Vec<_>>();
+                println!("Devices: {devices:#?}");
+                Ok::<_, eyre::Error>(())
+            },
+        )
         .await?;
 
     println!("Discovery completed");
@@ -204,11 +204,14 @@ class ObjectType extends RegisteredTypeBase {
       this.properties.add(
         new Property(
           propName,
-          typeCtx.handleOptType(
-            `${name}${propName}`,
-            propSchema,
-            required.includes(propName)
-          ),
+          // The spec declares `Raw` in the command methods as a string, but describes it as a boolean flag.
+          propName === 'Raw'
+            ? rusty('bool')
+            : typeCtx.handleOptType(
+                `${name}${propName}`,
+                propSchema,
+                required.includes(propName)
+              ),
           getDoc(nameAndTarget(propSchema).target)
         )
       );
@@ -258,6 +261,29 @@ class EnumVariant {
   }
 }
 
+// Enums whose numeric order is meaningful, so they can also derive `Ord`.
+const orderedEnums = new Set(['CameraState']);
+
+// Extra hand-written docs appended to the generated ones.
+const enumExamples: Record<string, string> = {
+  CameraState: `
+    ///
+    /// # Example
+    ///
+    /// \`\`\`
+    /// use ascom_alpaca::api::CameraState;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut time_in_state = HashMap::new();
+    /// *time_in_state.entry(CameraState::Exposing).or_insert(0) += 5;
+    /// assert_eq!(time_in_state[&CameraState::Exposing], 5);
+    ///
+    /// // States are ordered by their progression through an exposure.
+    /// assert!(CameraState::Exposing < CameraState::Download);
+    /// \`\`\`
+  `
+};
+
 class EnumType extends RegisteredTypeBase {
   public readonly variants = new NamedSet<EnumVariant>();
   public readonly baseType: RustType;
@@ -274,7 +300,8 @@ class EnumType extends RegisteredTypeBase {
   toString() {
     return `
       ${stringifyDoc(this.doc)}
-      ${this.stringifyCfg()}#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize_repr, Deserialize_repr, TryFromPrimitive, IntoPrimitive)]
+      ${enumExamples[this.name] ?? ''}
+      ${this.stringifyCfg()}#[derive(Debug, PartialEq, Eq, ${orderedEnums.has(this.name) ? 'PartialOrd, Ord, ' : ''}Hash, Clone, Copy, Serialize_repr, Deserialize_repr, TryFromPrimitive, IntoPrimitive)]
       #[repr(${this.baseType})]
       #[allow(missing_docs)] // some enum variants might not have docs and that's okay
       pub enum ${this.name} {
@@ -739,6 +766,18 @@ mod image_array;
 #[cfg(feature = "camera")]
 pub use image_array::*;
 
+#[cfg(feature = "camera")]
+mod camera_progress;
+
+#[cfg(feature = "camera")]
+pub use camera_progress::CameraProgress;
+
+#[cfg(feature = "client")]
+mod helpers;
+
+#[cfg(feature = "client")]
+pub use helpers::*;
+
 ${types}
 
 ${devices}
@@ -0,0 +1,123 @@
+use super::CameraState;
+use crate::{ASCOMError, ASCOMResult};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+enum Progress {
+    /// Progress estimated from the time elapsed since the start of the phase.
+    Timed {
+        start: Instant,
+        expected_duration: Duration,
+    },
+    /// Progress reported explicitly by the driver.
+    Explicit(i32),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Phase {
+    state: CameraState,
+    progress: Progress,
+}
+
+/// Tracker of the camera state and progress of the current operation, for use in drivers.
+///
+/// Update it as the operation moves through the `Waiting`, `Exposing`, `Reading` and `Download`
+/// states, and forward [`Camera::camera_state`](super::Camera::camera_state) and
+/// [`Camera::percent_completed`](super::Camera::percent_completed) to [`Self::state`] and
+/// [`Self::percent_completed`] so that clients see meaningful progress throughout the operation,
+/// not just during the exposure itself.
+///
+/// Once the operation is [finished](Self::finish), progress stays at 100% until the next one starts.
+#[derive(Debug)]
+pub struct CameraProgress {
+    phase: Mutex<Phase>,
+}
+
+impl Default for CameraProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CameraProgress {
+    /// Create a tracker in the idle state.
+    pub const fn new() -> Self {
+        Self {
+            phase: Mutex::new(Phase {
+                state: CameraState::Idle,
+                progress: Progress::Explicit(100),
+            }),
+        }
+    }
+
+    fn set(&self, phase: Phase) {
+        *self.phase.lock().unwrap_or_else(PoisonError::into_inner) = phase;
+    }
+
+    fn get(&self) -> Phase {
+        *self.phase.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Enter a phase whose progress is estimated from the time elapsed against `expected_duration`,
+    /// e.g. an exposure or a readout with known duration.
+    pub fn start_timed(&self, state: CameraState, expected_duration: Duration) {
+        self.set(Phase {
+            state,
+            progress: Progress::Timed {
+                start: Instant::now(),
+                expected_duration,
+            },
+        });
+    }
+
+    /// Enter or update a phase with explicitly reported progress in percent,
+    /// e.g. based on the number of bytes downloaded from the hardware.
+    ///
+    /// Values outside of `0..=100` are clamped.
+    pub fn set_progress(&self, state: CameraState, percent: i32) {
+        self.set(Phase {
+            state,
+            progress: Progress::Explicit(percent.clamp(0, 100)),
+        });
+    }
+
+    /// Mark the operation as successfully completed and return to the idle state.
+    pub fn finish(&self) {
+        self.set_progress(CameraState::Idle, 100);
+    }
+
+    /// Mark the camera as being in an error state.
+    pub fn fail(&self) {
+        self.set_progress(CameraState::Error, 0);
+    }
+
+    /// Current camera state.
+    pub fn state(&self) -> CameraState {
+        self.get().state
+    }
+
+    /// Progress of the current operation in percent.
+    ///
+    /// Returns an `INVALID_OPERATION` error if the camera is in an error state.
+    pub fn percent_completed(&self) -> ASCOMResult<i32> {
+        let phase = self.get();
+        match (phase.state, phase.progress) {
+            (CameraState::Error, _) => {
+                Err(ASCOMError::invalid_operation("camera is in an error state"))
+            }
+            (
+                _,
+                Progress::Timed {
+                    start,
+                    expected_duration,
+                },
+            ) => {
+                let percent =
+                    (start.elapsed().as_millis() * 100) / expected_duration.as_millis().max(1);
+                Ok(i32::try_from(percent.min(100)).unwrap_or(100))
+            }
+            (_, Progress::Explicit(percent)) => Ok(percent),
+        }
+    }
+}
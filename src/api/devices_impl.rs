@@ -1,4 +1,4 @@
-use super::{ConfiguredDevice, Device, DeviceType, Devices, TypedDevice};
+use super::{ConfiguredDevice, Device, DeviceDescriptor, DeviceType, Devices, TypedDevice};
 use serde::Serialize;
 use std::fmt::{Debug, Display};
 
@@ -7,7 +7,8 @@ pub(crate) trait RetrieavableDevice: 'static + Device /* where Self: Unsize<DynT
 
     fn get_storage(storage: &Devices) -> &[std::sync::Arc<Self>];
 
-    #[cfg(feature = "server")]
+    fn get_storage_mut(storage: &mut Devices) -> &mut Vec<std::sync::Arc<Self>>;
+
     fn to_configured_device(&self, as_number: usize) -> ConfiguredDevice<DeviceType> {
         ConfiguredDevice {
             name: self.static_name().to_owned(),
@@ -18,6 +19,30 @@ pub(crate) trait RetrieavableDevice: 'static + Device /* where Self: Unsize<DynT
     }
 }
 
+/// Whether the ID starts with a GUID in the canonical `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx` form.
+///
+/// Suffixes are allowed since drivers commonly append an index, e.g. `...-ae56210693e8::0`.
+fn starts_with_guid(unique_id: &str) -> bool {
+    let unique_id = unique_id.strip_prefix('{').unwrap_or(unique_id);
+    unique_id.as_bytes().get(..36).is_some_and(|guid| {
+        guid.iter().enumerate().all(|(i, &c)| match i {
+            8 | 13 | 18 | 23 => c == b'-',
+            _ => c.is_ascii_hexdigit(),
+        })
+    })
+}
+
+/// Warn driver authors in debug builds if a device's unique ID doesn't look like a GUID,
+/// as recommended by the ASCOM specification.
+pub(crate) fn check_unique_id(unique_id: &str) {
+    if cfg!(debug_assertions) && !starts_with_guid(unique_id) {
+        tracing::warn!(
+            unique_id,
+            "Device unique ID doesn't look like a GUID, which is recommended by the ASCOM specification"
+        );
+    }
+}
+
 /// A trait for devices that can be registered in a `Devices` storage.
 ///
 /// DynTrait is unused here, it's only necessary to cheat the type system
@@ -32,12 +57,53 @@ pub(crate) trait RegistrableDevice<DynTrait: ?Sized>: Debug {
 impl Devices {
     /// Register a device in the storage.
     ///
-    /// `device` can be an instance of any of the category traits (`Camera`, `Telescope`, etc.).
+    /// `device` can be an instance of any of the category traits (`Camera`, `Telescope`, etc.),
+    /// an `Arc` of such trait object, or a [`TypedDevice`], including ones returned by the client.
+    ///
+    /// In debug builds, a warning is logged if the device's [`unique_id`](Device::unique_id)
+    /// doesn't start with a GUID, which usually indicates a copy-paste or formatting mistake.
+    /// The check never rejects the device and can't be turned off, but it's compiled out of
+    /// release builds. Devices passed as a [`TypedDevice`] or an `Arc` of a trait object aren't
+    /// checked, as those usually come from another server.
     #[tracing::instrument(level = "debug", skip(self))]
     pub fn register<DynTrait: ?Sized>(&mut self, device: impl RegistrableDevice<DynTrait>) {
         device.add_to(self);
     }
 
+    /// Register a shared device instance under the given category.
+    ///
+    /// Unlike [`register`](Self::register), this accepts an existing `Arc`, so the same instance
+    /// can be registered under each category it implements, e.g. a camera with an integrated
+    /// filter wheel:
+    ///
+    /// ```ignore
+    /// let device = Arc::new(MyCameraWithFilterWheel::new());
+    /// devices.register_shared::<dyn Camera>(device.clone());
+    /// devices.register_shared::<dyn FilterWheel>(device);
+    /// ```
+    ///
+    /// Device numbers are assigned per category, so the device may end up with different numbers
+    /// in each of them. It will report the same [`unique_id`](Device::unique_id) in all categories,
+    /// so clients should identify such devices by the combination of category and unique ID.
+    pub fn register_shared<DynTrait: ?Sized + RetrieavableDevice>(
+        &mut self,
+        device: std::sync::Arc<DynTrait>,
+    ) {
+        check_unique_id(device.unique_id());
+        DynTrait::get_storage_mut(self).push(device);
+    }
+
+    /// Describe all registered devices for persisting e.g. an equipment profile.
+    ///
+    /// Device numbers are indices within each category of this collection, so they match
+    /// the server's own numbering when the collection was populated from a single server
+    /// via `Client::get_devices`.
+    pub fn descriptors(&self) -> Vec<DeviceDescriptor> {
+        self.iter_all()
+            .map(|(device, number)| device.to_configured_device(number).into())
+            .collect()
+    }
+
     /// Iterate over all devices of a given type.
     pub fn iter<DynTrait: ?Sized + RetrieavableDevice>(
         &self,
@@ -47,6 +113,17 @@ impl Devices {
             .map(std::sync::Arc::clone)
     }
 
+    /// Find a device by its [`unique_id`](Device::unique_id) across all categories.
+    ///
+    /// This is useful for reconnecting to a previously selected device, as the unique ID stays
+    /// the same across restarts while device numbers might not. If the same device is registered
+    /// under several categories, the first one is returned.
+    pub fn find_by_unique_id(&self, unique_id: &str) -> Option<TypedDevice> {
+        self.iter_all()
+            .map(|(device, _number)| device)
+            .find(|device| device.unique_id() == unique_id)
+    }
+
     /// Retrieve a device by its category trait and an index within that category.
     ///
     /// Example: `devices.get::<dyn Camera>(0)` returns the first camera in the storage.
@@ -131,3 +208,40 @@ impl FromIterator<TypedDevice> for Devices {
         devices
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::starts_with_guid;
+
+    #[test]
+    fn guid_unique_ids() {
+        for unique_id in [
+            "b5a6c0e1-8f3d-4e2a-9c71-ae56210693e8",
+            "b5a6c0e1-8f3d-4e2a-9c71-ae56210693e8::0",
+            "B5A6C0E1-8F3D-4E2A-9C71-AE56210693E8",
+            "{b5a6c0e1-8f3d-4e2a-9c71-ae56210693e8}",
+        ] {
+            assert!(
+                starts_with_guid(unique_id),
+                "{unique_id} should be accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn non_guid_unique_ids() {
+        for unique_id in [
+            "",
+            "b5a6c0e1-8f3d-4e2a-9c71-ae56210693e",
+            "g5a6c0e1-8f3d-4e2a-9c71-ae56210693e8",
+            "b5a6c0e18f3d4e2a9c71ae56210693e8::00",
+            "b5a6c0e1_8f3d_4e2a_9c71_ae56210693e8",
+            "my-camera",
+        ] {
+            assert!(
+                !starts_with_guid(unique_id),
+                "{unique_id} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Lightweight equatorial frame conversions for client helpers.
+//!
+//! Precession uses the IAU 1976 angles and nutation uses the low-precision series
+//! from Meeus, "Astronomical Algorithms", chapters 21-22. Aberration, proper motion
+//! and refraction are not applied, so results are accurate to roughly 20 arcseconds,
+//! which is plenty for slewing but not for precise astrometry.
+
+use crate::api::EquatorialCoordinateType;
+use crate::{ASCOMError, ASCOMResult};
+use std::time::SystemTime;
+
+type Matrix = [[f64; 3]; 3];
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Julian date of the J2000.0 epoch.
+const J2000: f64 = 2_451_545.0;
+/// Julian date of the J2050.0 epoch.
+const J2050: f64 = J2000 + 50.0 * 365.25;
+/// Julian date of the Unix epoch.
+const UNIX_EPOCH: f64 = 2_440_587.5;
+const DAYS_PER_CENTURY: f64 = 36_525.0;
+const SECONDS_PER_DAY: f64 = 86_400.0;
+const ARCSEC: f64 = 1.0 / 3600.0;
+
+fn rotate_x(angle: f64) -> Matrix {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, cos, sin], [0.0, -sin, cos]]
+}
+
+fn rotate_y(angle: f64) -> Matrix {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    [[cos, 0.0, -sin], [0.0, 1.0, 0.0], [sin, 0.0, cos]]
+}
+
+fn rotate_z(angle: f64) -> Matrix {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    [[cos, sin, 0.0], [-sin, cos, 0.0], [0.0, 0.0, 1.0]]
+}
+
+fn multiply(lhs: &Matrix, rhs: &Matrix) -> Matrix {
+    std::array::from_fn(|row| {
+        std::array::from_fn(|col| (0..3).map(|k| lhs[row][k] * rhs[k][col]).sum())
+    })
+}
+
+fn transpose(matrix: &Matrix) -> Matrix {
+    std::array::from_fn(|row| std::array::from_fn(|col| matrix[col][row]))
+}
+
+/// Julian centuries since J2000.0.
+fn centuries(julian_date: f64) -> f64 {
+    (julian_date - J2000) / DAYS_PER_CENTURY
+}
+
+/// Rotation from the J2000 mean equator and equinox to the mean equator and equinox of date.
+fn precession(julian_date: f64) -> Matrix {
+    let t = centuries(julian_date);
+    let zeta = t * t.mul_add(t.mul_add(0.017_998, 0.301_88), 2_306.218_1) * ARCSEC;
+    let z = t * t.mul_add(t.mul_add(0.018_203, 1.094_68), 2_306.218_1) * ARCSEC;
+    let theta = t * t.mul_add(t.mul_add(-0.041_833, -0.426_65), 2_004.310_9) * ARCSEC;
+    multiply(&rotate_z(-z), &multiply(&rotate_y(theta), &rotate_z(-zeta)))
+}
+
+/// Rotation from the mean equator and equinox of date to the true equator and equinox of date.
+fn nutation(julian_date: f64) -> Matrix {
+    let t = centuries(julian_date);
+    let moon_node = (-1_934.136_261_f64).mul_add(t, 125.044_52).to_radians();
+    let sun_longitude = 36_000.769_8_f64.mul_add(t, 280.466_5).to_radians();
+    let moon_longitude = 481_267.881_3_f64.mul_add(t, 218.316_5).to_radians();
+
+    let series = |terms: [(f64, f64); 4]| -> f64 {
+        terms.iter().map(|(coef, value)| coef * value).sum::<f64>() * ARCSEC
+    };
+    let nutation_in_longitude = series([
+        (-17.20, moon_node.sin()),
+        (-1.32, (2.0 * sun_longitude).sin()),
+        (-0.23, (2.0 * moon_longitude).sin()),
+        (0.21, (2.0 * moon_node).sin()),
+    ]);
+    let nutation_in_obliquity = series([
+        (9.20, moon_node.cos()),
+        (0.57, (2.0 * sun_longitude).cos()),
+        (0.10, (2.0 * moon_longitude).cos()),
+        (-0.09, (2.0 * moon_node).cos()),
+    ]);
+    let mean_obliquity = (-46.815_0 * ARCSEC).mul_add(t, 23.439_291_1);
+
+    multiply(
+        &rotate_x(-(mean_obliquity + nutation_in_obliquity)),
+        &multiply(&rotate_z(-nutation_in_longitude), &rotate_x(mean_obliquity)),
+    )
+}
+
+/// Rotation from J2000 to the given frame at the given Julian date.
+fn from_j2000(frame: EquatorialCoordinateType, julian_date: f64) -> ASCOMResult<Matrix> {
+    Ok(match frame {
+        EquatorialCoordinateType::J2000 => IDENTITY,
+        EquatorialCoordinateType::J2050 => precession(J2050),
+        EquatorialCoordinateType::Topocentric => {
+            multiply(&nutation(julian_date), &precession(julian_date))
+        }
+        EquatorialCoordinateType::Other | EquatorialCoordinateType::B1950 => {
+            return Err(ASCOMError::invalid_value(format_args!(
+                "conversion to or from {frame:?} coordinates is not supported"
+            )));
+        }
+    })
+}
+
+fn julian_date(time: SystemTime) -> ASCOMResult<f64> {
+    let since_unix_epoch = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(ASCOMError::invalid_value)?;
+    Ok(since_unix_epoch.as_secs_f64() / SECONDS_PER_DAY + UNIX_EPOCH)
+}
+
+/// Apply a rotation to right ascension (hours) and declination (degrees).
+fn rotate((right_ascension, declination): (f64, f64), rotation: &Matrix) -> (f64, f64) {
+    let (sin_ra, cos_ra) = (right_ascension * 15.0).to_radians().sin_cos();
+    let (sin_dec, cos_dec) = declination.to_radians().sin_cos();
+    let vector = [cos_dec * cos_ra, cos_dec * sin_ra, sin_dec];
+    let [x, y, z]: [f64; 3] =
+        std::array::from_fn(|row| (0..3).map(|k| rotation[row][k] * vector[k]).sum());
+
+    (
+        (y.atan2(x).to_degrees() / 15.0).rem_euclid(24.0),
+        z.clamp(-1.0, 1.0).asin().to_degrees(),
+    )
+}
+
+/// Convert right ascension (hours) and declination (degrees) between equatorial frames at the given time.
+pub(crate) fn convert(
+    coordinates: (f64, f64),
+    from: EquatorialCoordinateType,
+    to: EquatorialCoordinateType,
+    time: SystemTime,
+) -> ASCOMResult<(f64, f64)> {
+    if from == to {
+        return Ok(coordinates);
+    }
+
+    let julian_date = julian_date(time)?;
+    let rotation = multiply(
+        &from_j2000(to, julian_date)?,
+        &transpose(&from_j2000(from, julian_date)?),
+    );
+    Ok(rotate(coordinates, &rotation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// θ Persei from Meeus examples 21.b and 23.a, with proper motion already applied.
+    const THETA_PERSEI_J2000: (f64, f64) = (
+        2.0 + 44.0 / 60.0 + 12.975 / 3600.0,
+        49.0 + 13.0 / 60.0 + 39.90 / 3600.0,
+    );
+    /// 2028 November 13.19 TD.
+    const EXAMPLE_DATE: f64 = 2_462_088.69;
+
+    #[track_caller]
+    fn assert_close(actual: (f64, f64), expected: (f64, f64), tolerance_arcsec: f64) {
+        let ra_error = (actual.0 - expected.0) * 15.0 * expected.1.to_radians().cos() / ARCSEC;
+        let dec_error = (actual.1 - expected.1) / ARCSEC;
+        assert!(
+            ra_error.abs() < tolerance_arcsec && dec_error.abs() < tolerance_arcsec,
+            "{actual:?} is off from {expected:?} by ({ra_error:.2}\", {dec_error:.2}\")"
+        );
+    }
+
+    #[test]
+    fn precession_matches_meeus() {
+        // Example 21.b: mean place for the equinox of date.
+        let expected = (
+            2.0 + 46.0 / 60.0 + 11.331 / 3600.0,
+            49.0 + 20.0 / 60.0 + 54.54 / 3600.0,
+        );
+        assert_close(
+            rotate(THETA_PERSEI_J2000, &precession(EXAMPLE_DATE)),
+            expected,
+            0.1,
+        );
+    }
+
+    #[test]
+    fn topocentric_conversion_matches_meeus() {
+        // Example 23.a: mean place from example 21.b plus nutation (15.843", 6.218").
+        // Aberration isn't applied, so it's not part of the expected value.
+        let expected = (
+            2.0 + 46.0 / 60.0 + (11.331 + 15.843 / 15.0) / 3600.0,
+            49.0 + 20.0 / 60.0 + (54.54 + 6.218) / 3600.0,
+        );
+        let time = SystemTime::UNIX_EPOCH
+            + Duration::from_secs_f64((EXAMPLE_DATE - UNIX_EPOCH) * SECONDS_PER_DAY);
+        let actual = convert(
+            THETA_PERSEI_J2000,
+            EquatorialCoordinateType::J2000,
+            EquatorialCoordinateType::Topocentric,
+            time,
+        )
+        .expect("conversion should succeed");
+        assert_close(actual, expected, 20.0);
+
+        let round_trip = convert(
+            actual,
+            EquatorialCoordinateType::Topocentric,
+            EquatorialCoordinateType::J2000,
+            time,
+        )
+        .expect("conversion should succeed");
+        assert_close(round_trip, THETA_PERSEI_J2000, 0.01);
+    }
+}
@@ -0,0 +1,431 @@
+use super::{if_implemented, poll_until, with_deadline, WaitOptions};
+use crate::api::{Camera, CameraState, ImageArray, SensorType};
+use crate::{ASCOMError, ASCOMErrorCode, ASCOMResult, ASCOMResultExt};
+use futures::Stream;
+use ndarray::Array3;
+use std::time::{Duration, SystemTime};
+
+/// Exposure and camera settings describing the last captured frame.
+///
+/// Returned by the `frame_metadata` helper on [`dyn Camera`](Camera). Properties the camera
+/// doesn't implement are left as `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameMetadata {
+    /// Start time of the last exposure.
+    pub exposure_start_time: Option<SystemTime>,
+    /// Duration of the last exposure in seconds.
+    pub exposure_duration: Option<f64>,
+    /// Current gain value.
+    pub gain: Option<i32>,
+    /// Current offset value.
+    pub offset: Option<i32>,
+    /// Current binning on the X axis.
+    pub bin_x: Option<i32>,
+    /// Current binning on the Y axis.
+    pub bin_y: Option<i32>,
+}
+
+/// Camera state together with the progress of the current operation, ready for display.
+///
+/// Returned by the `status` helper on [`dyn Camera`](Camera).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CameraStatus {
+    /// Current camera state.
+    pub state: CameraState,
+    /// Progress of the current operation in percent, if reported by the camera.
+    pub percent: Option<i32>,
+    /// Human-readable description of the state, e.g. `Exposing 45%`.
+    pub message: String,
+}
+
+impl CameraStatus {
+    fn new(state: CameraState, percent: Option<i32>) -> Self {
+        let description = match state {
+            CameraState::Idle => "Idle",
+            CameraState::Waiting => "Waiting",
+            CameraState::Exposing => "Exposing",
+            CameraState::Reading => "Reading out",
+            CameraState::Download => "Downloading",
+            CameraState::Error => "Error",
+        };
+        let message = match (state, percent) {
+            (CameraState::Idle | CameraState::Error, _) | (_, None) => description.to_owned(),
+            (_, Some(percent)) => format!("{description} {percent}%"),
+        };
+        Self {
+            state,
+            percent,
+            message,
+        }
+    }
+}
+
+/// Unified view of the camera readout configuration.
+///
+/// ASCOM cameras expose readout speed either as a simple fast/normal toggle
+/// ([`Camera::can_fast_readout`] and [`Camera::fast_readout`]) or as a list of named modes
+/// ([`Camera::readout_modes`] and [`Camera::readout_mode`]), but never both.
+///
+/// Returned by the `readout_config` helper on [`dyn Camera`](Camera).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadoutConfig {
+    /// The camera supports a fast/normal readout toggle.
+    FastToggle {
+        /// Whether fast readout is currently enabled.
+        fast: bool,
+    },
+    /// The camera supports a list of named readout modes.
+    Modes {
+        /// Names of the available modes.
+        modes: Vec<String>,
+        /// Index of the currently selected mode in `modes`.
+        current: usize,
+    },
+    /// The camera doesn't support any readout configuration.
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Roi {
+    bin_x: i32,
+    bin_y: i32,
+    start_x: i32,
+    start_y: i32,
+    num_x: i32,
+    num_y: i32,
+}
+
+fn check_axis(axis: char, start: i32, num: i32, binned_size: i32) -> ASCOMResult {
+    if start < 0 || num < 1 || start.saturating_add(num) > binned_size {
+        return Err(ASCOMError::invalid_value(format_args!(
+            "subframe {axis} range {start}..{end} doesn't fit into 0..{binned_size} binned pixels",
+            end = start.saturating_add(num),
+        )));
+    }
+    Ok(())
+}
+
+impl Roi {
+    async fn read(camera: &dyn Camera) -> ASCOMResult<Self> {
+        Ok(Self {
+            bin_x: camera.bin_x().await?,
+            bin_y: camera.bin_y().await?,
+            start_x: camera.start_x().await?,
+            start_y: camera.start_y().await?,
+            num_x: camera.num_x().await?,
+            num_y: camera.num_y().await?,
+        })
+    }
+
+    async fn apply(self, camera: &dyn Camera) -> ASCOMResult {
+        // Move the subframe origin to the corner first so that it's valid under any binning,
+        // then change binning, and only then set the size and final position in new binned units.
+        camera
+            .set_start_x(0)
+            .await
+            .context("failed to set StartX to 0")?;
+        camera
+            .set_start_y(0)
+            .await
+            .context("failed to set StartY to 0")?;
+        camera
+            .set_bin_x(self.bin_x)
+            .await
+            .context(format_args!("failed to set BinX to {}", self.bin_x))?;
+        camera
+            .set_bin_y(self.bin_y)
+            .await
+            .context(format_args!("failed to set BinY to {}", self.bin_y))?;
+        camera
+            .set_num_x(self.num_x)
+            .await
+            .context(format_args!("failed to set NumX to {}", self.num_x))?;
+        camera
+            .set_num_y(self.num_y)
+            .await
+            .context(format_args!("failed to set NumY to {}", self.num_y))?;
+        camera
+            .set_start_x(self.start_x)
+            .await
+            .context(format_args!("failed to set StartX to {}", self.start_x))?;
+        camera
+            .set_start_y(self.start_y)
+            .await
+            .context(format_args!("failed to set StartY to {}", self.start_y))?;
+        Ok(())
+    }
+}
+
+impl dyn Camera {
+    /// Get the current readout configuration, whichever of the two mechanisms the camera supports.
+    pub async fn readout_config(&self) -> ASCOMResult<ReadoutConfig> {
+        if self.can_fast_readout().await? {
+            return Ok(ReadoutConfig::FastToggle {
+                fast: self.fast_readout().await?,
+            });
+        }
+        let Some(modes) = if_implemented(self.readout_modes().await)? else {
+            return Ok(ReadoutConfig::Unsupported);
+        };
+        let current = self.readout_mode().await?;
+        Ok(ReadoutConfig::Modes {
+            current: usize::try_from(current)
+                .ok()
+                .filter(|&current| current < modes.len())
+                .ok_or_else(|| {
+                    ASCOMError::invalid_value(format_args!(
+                        "camera reported readout mode {current} outside of its {} modes",
+                        modes.len()
+                    ))
+                })?,
+            modes,
+        })
+    }
+
+    /// Enable or disable fast readout, checking [`Camera::can_fast_readout`] first.
+    ///
+    /// Returns an `INVALID_OPERATION` error if the camera only supports named readout modes.
+    pub async fn set_fast_readout_checked(&self, fast: bool) -> ASCOMResult {
+        if !self.can_fast_readout().await? {
+            return Err(ASCOMError::invalid_operation(
+                "camera doesn't support fast readout toggle, use readout modes instead",
+            ));
+        }
+        self.set_fast_readout(fast).await
+    }
+
+    /// Select a readout mode by its name from [`Camera::readout_modes`].
+    ///
+    /// Names are compared case-insensitively. Returns an `INVALID_OPERATION` error if the camera
+    /// uses the fast readout toggle instead, or `INVALID_VALUE` if there's no such mode.
+    pub async fn set_readout_mode_by_name(&self, name: &str) -> ASCOMResult {
+        if self.can_fast_readout().await? {
+            return Err(ASCOMError::invalid_operation(
+                "camera uses fast readout toggle instead of readout modes",
+            ));
+        }
+        let modes = self.readout_modes().await?;
+        let index = modes
+            .iter()
+            .position(|mode| mode.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                ASCOMError::invalid_value(format_args!(
+                    "unknown readout mode {name:?}, available modes: {modes:?}"
+                ))
+            })?;
+        self.set_readout_mode(i32::try_from(index).map_err(ASCOMError::invalid_value)?)
+            .await
+    }
+
+    /// Read the camera state and progress of the current operation in one go.
+    ///
+    /// Both properties are requested concurrently. [`Camera::percent_completed`] is optional
+    /// and may also be invalid while the camera is idle, so its errors result in `percent` being `None`.
+    pub async fn status(&self) -> ASCOMResult<CameraStatus> {
+        let (state, percent) = futures::join!(self.camera_state(), self.percent_completed());
+        let state = state?;
+        let percent = match percent {
+            Ok(percent) => Some(percent),
+            Err(err)
+                if err.code == ASCOMErrorCode::NOT_IMPLEMENTED
+                    || err.code == ASCOMErrorCode::INVALID_OPERATION =>
+            {
+                None
+            }
+            Err(err) => return Err(err),
+        };
+        Ok(CameraStatus::new(state, percent))
+    }
+
+    /// Fetch the metadata of the last exposure, e.g. for writing FITS headers.
+    ///
+    /// All properties are requested concurrently.
+    pub async fn frame_metadata(&self) -> ASCOMResult<FrameMetadata> {
+        let (exposure_start_time, exposure_duration, gain, offset, bin_x, bin_y) = futures::join!(
+            self.last_exposure_start_time(),
+            self.last_exposure_duration(),
+            self.gain(),
+            self.offset(),
+            self.bin_x(),
+            self.bin_y(),
+        );
+        Ok(FrameMetadata {
+            exposure_start_time: if_implemented(exposure_start_time)?,
+            exposure_duration: if_implemented(exposure_duration)?,
+            gain: if_implemented(gain)?,
+            offset: if_implemented(offset)?,
+            bin_x: if_implemented(bin_x)?,
+            bin_y: if_implemented(bin_y)?,
+        })
+    }
+
+    /// Set binning and subframe in one go.
+    ///
+    /// `start_x`, `start_y`, `num_x` and `num_y` are in binned pixels, as in the corresponding
+    /// [`Camera`] properties. The whole region is validated against the sensor size and maximum
+    /// binning before anything is changed, and the properties are then set in an order that
+    /// keeps the intermediate states valid.
+    ///
+    /// If the device rejects any of the values, the previous binning and subframe are restored
+    /// on a best-effort basis and the returned error names the property that failed.
+    pub async fn set_roi(
+        &self,
+        bin: i32,
+        start_x: i32,
+        start_y: i32,
+        num_x: i32,
+        num_y: i32,
+    ) -> ASCOMResult {
+        let max_bin = self.max_bin_x().await?.min(self.max_bin_y().await?);
+        if !(1..=max_bin).contains(&bin) {
+            return Err(ASCOMError::invalid_value(format_args!(
+                "binning {bin} is out of range 1..={max_bin}"
+            )));
+        }
+        check_axis('X', start_x, num_x, self.camera_xsize().await? / bin)?;
+        check_axis('Y', start_y, num_y, self.camera_ysize().await? / bin)?;
+
+        let previous = Roi::read(self).await?;
+        let target = Roi {
+            bin_x: bin,
+            bin_y: bin,
+            start_x,
+            start_y,
+            num_x,
+            num_y,
+        };
+        if let Err(err) = target.apply(self).await {
+            if let Err(rollback_err) = previous.apply(self).await {
+                tracing::warn!(%rollback_err, ?previous, "Failed to restore previous camera ROI");
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// Download the last image and convert it to RGB according to the camera's [`SensorType`].
+    ///
+    /// Color images are passed through, monochrome images are replicated into all three channels,
+    /// and Bayer images are demosaiced via [`ImageArray::debayer`], taking
+    /// [`Camera::bayer_offset_x`] and [`Camera::bayer_offset_y`] into account.
+    ///
+    /// The returned array is indexed as `[x, y, channel]`, like [`ImageArray`](crate::api::ImageArray),
+    /// with values clamped to the `u16` range.
+    pub async fn image_array_rgb(&self) -> ASCOMResult<Array3<u16>> {
+        let sensor_type = self.sensor_type().await?;
+        let bayer_offset = match sensor_type {
+            SensorType::Monochrome | SensorType::Color => (0, 0),
+            _ => (self.bayer_offset_x().await?, self.bayer_offset_y().await?),
+        };
+        self.image_array()
+            .await?
+            .to_rgb_u16(sensor_type, bayer_offset)
+    }
+
+    /// Capture `count` dark frames for each of the given exposure `durations` in seconds.
+    ///
+    /// Exposures are started with `light` set to `false` so that the shutter, if any, stays closed,
+    /// and [`Camera::image_ready`] is polled every `poll_interval` until each frame can be downloaded.
+    ///
+    /// Cameras without a mechanical shutter can't block light on their own, so a warning is logged
+    /// for them and the sensor has to be covered manually for the frames to be meaningful.
+    ///
+    /// The stream ends after the first error.
+    pub fn capture_dark_frames<'camera>(
+        &'camera self,
+        durations: impl 'camera + IntoIterator<Item = f64>,
+        count: usize,
+        poll_interval: Duration,
+    ) -> impl 'camera + Stream<Item = ASCOMResult<ImageArray>> {
+        async_fn_stream::try_fn_stream(move |emitter| async move {
+            self.warn_if_no_shutter().await?;
+            for duration in durations {
+                for _ in 0..count {
+                    emitter
+                        .emit(
+                            self.capture_calibration_frame(duration, poll_interval)
+                                .await?,
+                        )
+                        .await;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Capture `count` bias frames, i.e. dark frames with the shortest exposure the camera supports.
+    ///
+    /// See [`capture_dark_frames`](Self::capture_dark_frames) for details.
+    pub fn capture_bias_frames(
+        &self,
+        count: usize,
+        poll_interval: Duration,
+    ) -> impl '_ + Stream<Item = ASCOMResult<ImageArray>> {
+        async_fn_stream::try_fn_stream(move |emitter| async move {
+            self.warn_if_no_shutter().await?;
+            let duration = self.exposure_min().await?;
+            for _ in 0..count {
+                emitter
+                    .emit(
+                        self.capture_calibration_frame(duration, poll_interval)
+                            .await?,
+                    )
+                    .await;
+            }
+            Ok(())
+        })
+    }
+
+    async fn warn_if_no_shutter(&self) -> ASCOMResult {
+        if !self.has_shutter().await? {
+            tracing::warn!(
+                "Camera has no mechanical shutter, calibration frames are only meaningful if the sensor is covered"
+            );
+        }
+        Ok(())
+    }
+
+    async fn capture_calibration_frame(
+        &self,
+        duration: f64,
+        poll_interval: Duration,
+    ) -> ASCOMResult<ImageArray> {
+        self.start_exposure(duration, false)
+            .await
+            .context(format_args!(
+                "failed to start {duration}s calibration exposure"
+            ))?;
+        self.wait_for_image(poll_interval).await
+    }
+
+    /// Wait for the current exposure to complete and download the image.
+    ///
+    /// [`Camera::image_ready`] is polled every [`WaitOptions::poll_interval`], failing fast
+    /// if the camera enters [`CameraState::Error`] in the meantime. If the deadline is exceeded,
+    /// the exposure is aborted when [`WaitOptions::abort_on_timeout`] is set.
+    ///
+    /// Dropping the returned future stops polling, but leaves the exposure running.
+    pub async fn wait_for_image(
+        &self,
+        wait_options: impl Into<WaitOptions>,
+    ) -> ASCOMResult<ImageArray> {
+        let wait_options = wait_options.into();
+        with_deadline(
+            wait_options,
+            poll_until(wait_options.poll_interval, || async {
+                if self.image_ready().await? {
+                    return Ok(true);
+                }
+                if self.camera_state().await? == CameraState::Error {
+                    return Err(ASCOMError::unspecified(
+                        "camera entered an error state while waiting for the image",
+                    ));
+                }
+                Ok(false)
+            }),
+            || self.abort_exposure(),
+        )
+        .await?;
+        self.image_array().await
+    }
+}
@@ -0,0 +1,184 @@
+use super::poll_until;
+use crate::api::Device;
+use crate::{ASCOMError, ASCOMErrorCode, ASCOMResult};
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+const CONNECTING_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy)]
+enum ConnectionMethod {
+    /// Platform 7 `Connect` / `Disconnect` methods with `Connecting` as the completion property.
+    Connect,
+    /// Legacy `Connected` property setter.
+    SetConnected,
+}
+
+async fn wait_while_connecting(device: &(impl ?Sized + Device)) -> ASCOMResult {
+    poll_until(CONNECTING_POLL_INTERVAL, || async {
+        Ok(!device.connecting().await?)
+    })
+    .await
+}
+
+impl ConnectionMethod {
+    async fn connect(device: &(impl ?Sized + Device)) -> ASCOMResult<Self> {
+        match device.connect().await {
+            Ok(()) => {
+                wait_while_connecting(device).await?;
+                Ok(Self::Connect)
+            }
+            Err(err) if err.code == ASCOMErrorCode::NOT_IMPLEMENTED => {
+                device.set_connected(true).await?;
+                Ok(Self::SetConnected)
+            }
+            // Platform 6 servers not built with this crate reject the unknown `connect`
+            // method at the HTTP level (usually with 400 Bad Request), which the client
+            // reports as an unspecified error rather than `NOT_IMPLEMENTED`.
+            Err(err) if err.code == ASCOMErrorCode::UNSPECIFIED => {
+                match device.set_connected(true).await {
+                    Ok(()) => Ok(Self::SetConnected),
+                    // Both failed, so the original error is likely the real problem.
+                    Err(_) => Err(err),
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn disconnect(self, device: &(impl ?Sized + Device)) -> ASCOMResult {
+        match self {
+            Self::Connect => {
+                device.disconnect().await?;
+                wait_while_connecting(device).await
+            }
+            Self::SetConnected => device.set_connected(false).await,
+        }
+    }
+}
+
+/// Guard that keeps a device connected for as long as it's alive.
+///
+/// Returned by [`DeviceExt::connect_scoped`]. Dereferences to the underlying device.
+///
+/// When dropped, the guard disconnects the device in a background task, so early returns
+/// and errors don't leave the hardware connected. Use [`ConnectedGuard::disconnect`] instead
+/// to wait for the disconnection and observe its result.
+///
+/// If the device was already connected when the guard was created (e.g. by another client
+/// sharing the same hardware), it's left connected.
+#[derive(Debug)]
+pub struct ConnectedGuard<D: ?Sized + Device + 'static> {
+    device: Arc<D>,
+    /// How to disconnect the device, or `None` if it shouldn't be disconnected.
+    method: Option<ConnectionMethod>,
+}
+
+impl<D: ?Sized + Device + 'static> ConnectedGuard<D> {
+    /// Disconnect the device now and wait for the disconnection to complete.
+    pub async fn disconnect(mut self) -> ASCOMResult {
+        match self.method.take() {
+            Some(method) => method.disconnect(&*self.device).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Return the device, leaving it connected.
+    pub fn into_inner(mut self) -> Arc<D> {
+        self.method = None;
+        Arc::clone(&self.device)
+    }
+}
+
+impl<D: ?Sized + Device + 'static> Deref for ConnectedGuard<D> {
+    type Target = D;
+
+    fn deref(&self) -> &D {
+        &self.device
+    }
+}
+
+impl<D: ?Sized + Device + 'static> Drop for ConnectedGuard<D> {
+    fn drop(&mut self) {
+        let Some(method) = self.method.take() else {
+            return;
+        };
+        let device = Arc::clone(&self.device);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn(async move {
+                if let Err(err) = method.disconnect(&*device).await {
+                    tracing::warn!(%err, "Failed to disconnect device on guard drop");
+                }
+            })),
+            Err(err) => {
+                tracing::warn!(%err, "Can't disconnect device on guard drop outside of Tokio runtime");
+            }
+        }
+    }
+}
+
+/// Client-side helpers available on all device types.
+///
+/// This trait is implemented for every [`Device`], including trait objects like `dyn Camera`.
+#[async_trait::async_trait]
+pub trait DeviceExt: Device {
+    /// Check whether the device lists the given action in [`Device::supported_actions`].
+    ///
+    /// Action names are compared case-insensitively. The list is only fetched once per device
+    /// if the client was built with [`ClientBuilder::cache_supported_actions`](crate::ClientBuilder::cache_supported_actions).
+    async fn has_action(&self, name: &str) -> ASCOMResult<bool> {
+        let has = |actions: &[String]| {
+            actions
+                .iter()
+                .any(|supported| supported.eq_ignore_ascii_case(name))
+        };
+
+        let Some(cache) = self.supported_actions_cache() else {
+            return Ok(has(&self.supported_actions().await?));
+        };
+        if let Some(actions) = cache.get() {
+            return Ok(has(actions));
+        }
+        let actions = self.supported_actions().await?;
+        Ok(has(cache.get_or_init(|| actions)))
+    }
+
+    /// Invoke [`Device::action`] after checking that the device supports it.
+    ///
+    /// Returns an [`ACTION_NOT_IMPLEMENTED`](ASCOMErrorCode::ACTION_NOT_IMPLEMENTED) error naming
+    /// the action if it's not listed in [`Device::supported_actions`].
+    async fn checked_action(&self, action: String, parameters: String) -> ASCOMResult<String> {
+        if !self.has_action(&action).await? {
+            return Err(ASCOMError::new(
+                ASCOMErrorCode::ACTION_NOT_IMPLEMENTED,
+                format_args!("action {action:?} is not supported by the device"),
+            ));
+        }
+        self.action(action, parameters).await
+    }
+
+    /// Connect the device and return a guard that disconnects it when dropped.
+    ///
+    /// Uses the Platform 7 [`Device::connect`] method and waits for [`Device::connecting`]
+    /// to clear, falling back to [`Device::set_connected`] on older devices, including
+    /// servers that reject `connect` as an unknown HTTP route.
+    ///
+    /// See [`ConnectedGuard`] for details.
+    async fn connect_scoped(self: Arc<Self>) -> ASCOMResult<ConnectedGuard<Self>>
+    where
+        Self: 'static,
+    {
+        let method = if self.connected().await? {
+            None
+        } else {
+            Some(ConnectionMethod::connect(&*self).await?)
+        };
+        Ok(ConnectedGuard {
+            device: self,
+            method,
+        })
+    }
+}
+
+impl<T: ?Sized + Device> DeviceExt for T {}
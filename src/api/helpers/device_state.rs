@@ -0,0 +1,253 @@
+use crate::api::time_repr::{Iso8601, TimeRepr};
+#[cfg(feature = "filterwheel")]
+use crate::api::FilterWheel;
+#[cfg(feature = "focuser")]
+use crate::api::Focuser;
+#[cfg(feature = "observingconditions")]
+use crate::api::ObservingConditions;
+#[cfg(feature = "rotator")]
+use crate::api::Rotator;
+#[cfg(feature = "safetymonitor")]
+use crate::api::SafetyMonitor;
+#[cfg(feature = "switch")]
+use crate::api::Switch;
+#[cfg(feature = "covercalibrator")]
+use crate::api::{CalibratorStatus, CoverCalibrator, CoverStatus};
+#[cfg(feature = "camera")]
+use crate::api::{Camera, CameraState};
+use crate::api::{Device, DeviceStateItem};
+#[cfg(feature = "dome")]
+use crate::api::{Dome, ShutterState};
+#[cfg(feature = "telescope")]
+use crate::api::{PierSide, Telescope};
+use crate::ASCOMResult;
+use serde::de::DeserializeOwned;
+#[cfg(feature = "switch")]
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+/// Operational properties from a `devicestate` response, keyed by name.
+struct Items(HashMap<String, serde_json::Value>);
+
+impl Items {
+    /// Remove and decode the named property, ignoring it if it has an unexpected type.
+    fn take<T: DeserializeOwned>(&mut self, name: &str) -> Option<T> {
+        let value = self.0.remove(name)?;
+        match serde_json::from_value(value) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                tracing::warn!(name, %err, "Ignoring malformed operational property");
+                None
+            }
+        }
+    }
+}
+
+/// Operational state of a device fetched via the Platform 7 `devicestate` request.
+///
+/// Returned by the `operational_state` helper on each device trait object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimestampedDeviceState<T> {
+    /// Time at which the device captured the state, if reported.
+    pub timestamp: Option<SystemTime>,
+    /// Typed operational properties.
+    pub state: T,
+}
+
+impl<T> TimestampedDeviceState<T> {
+    fn from_items(items: Vec<DeviceStateItem>, parse: impl FnOnce(&mut Items) -> T) -> Self {
+        let mut items = Items(
+            items
+                .into_iter()
+                .map(|item| (item.name, item.value))
+                .collect(),
+        );
+        Self {
+            timestamp: items
+                .take::<TimeRepr<Iso8601>>("TimeStamp")
+                .map(SystemTime::from),
+            state: parse(&mut items),
+        }
+    }
+}
+
+macro_rules! device_states {
+    ($(
+        #[cfg(feature = $feature:literal)]
+        $trait_name:ident => $state_name:ident {
+            $($name:literal => $method:ident: $ty:ty $(as $via:ty)?,)*
+        }
+    )*) => {$(
+        #[doc = concat!("Operational properties of a [`", stringify!($trait_name), "`].")]
+        ///
+        /// Properties that the device didn't report are `None`.
+        #[cfg(feature = $feature)]
+        #[derive(Debug, Clone, Copy, Default, PartialEq)]
+        pub struct $state_name {
+            $(
+                #[doc = concat!("Value of [`", stringify!($trait_name), "::", stringify!($method), "`].")]
+                pub $method: Option<$ty>,
+            )*
+        }
+
+        #[cfg(feature = $feature)]
+        impl $state_name {
+            fn from_items(items: &mut Items) -> Self {
+                Self {
+                    $($method: device_states!(@take items $name $ty $(as $via)?),)*
+                }
+            }
+        }
+
+        #[cfg(feature = $feature)]
+        impl dyn $trait_name {
+            /// Read all operational properties in a single `devicestate` request.
+            ///
+            /// Requires a Platform 7 device; older devices return a `NOT_IMPLEMENTED` error.
+            pub async fn operational_state(
+                &self,
+            ) -> ASCOMResult<TimestampedDeviceState<$state_name>> {
+                Ok(TimestampedDeviceState::from_items(
+                    self.device_state().await?,
+                    $state_name::from_items,
+                ))
+            }
+        }
+    )*};
+
+    (@take $items:ident $name:literal $ty:ty) => {
+        $items.take::<$ty>($name)
+    };
+
+    (@take $items:ident $name:literal $ty:ty as $via:ty) => {
+        $items.take::<$via>($name).map(<$ty>::from)
+    };
+}
+
+crate::macros::operational_property_table!(device_states);
+
+/// Operational properties of a [`Switch`], keyed by switch ID.
+///
+/// Switches that the device didn't report are missing from the maps.
+#[cfg(feature = "switch")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SwitchDeviceState {
+    /// Values of [`Switch::get_switch`].
+    pub get_switch: BTreeMap<usize, bool>,
+    /// Values of [`Switch::get_switch_value`].
+    pub get_switch_value: BTreeMap<usize, f64>,
+    /// Values of [`Switch::state_change_complete`].
+    pub state_change_complete: BTreeMap<usize, bool>,
+}
+
+#[cfg(feature = "switch")]
+impl SwitchDeviceState {
+    fn from_items(items: &mut Items) -> Self {
+        let mut state = Self::default();
+        for name in items.0.keys().cloned().collect::<Vec<_>>() {
+            let parse_id = |prefix: &str| name.strip_prefix(prefix)?.parse::<usize>().ok();
+            if let Some(id) = parse_id("GetSwitchValue") {
+                state
+                    .get_switch_value
+                    .extend(items.take(&name).map(|value| (id, value)));
+            } else if let Some(id) = parse_id("GetSwitch") {
+                state
+                    .get_switch
+                    .extend(items.take(&name).map(|value| (id, value)));
+            } else if let Some(id) = parse_id("StateChangeComplete") {
+                state
+                    .state_change_complete
+                    .extend(items.take(&name).map(|value| (id, value)));
+            }
+        }
+        state
+    }
+}
+
+#[cfg(feature = "switch")]
+impl dyn Switch {
+    /// Read all operational properties in a single `devicestate` request.
+    ///
+    /// Requires a Platform 7 device; older devices return a `NOT_IMPLEMENTED` error.
+    pub async fn operational_state(
+        &self,
+    ) -> ASCOMResult<TimestampedDeviceState<SwitchDeviceState>> {
+        Ok(TimestampedDeviceState::from_items(
+            self.device_state().await?,
+            SwitchDeviceState::from_items,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn parse<T>(payload: &str, parse: impl FnOnce(&mut Items) -> T) -> TimestampedDeviceState<T> {
+        let items = serde_json::from_str(payload).expect("failed to parse device state");
+        TimestampedDeviceState::from_items(items, parse)
+    }
+
+    fn timestamp() -> Option<SystemTime> {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1_683_376_496_789))
+    }
+
+    #[cfg(feature = "camera")]
+    #[test]
+    fn camera_state() {
+        let state = parse(
+            r#"[
+                {"Name": "CameraState", "Value": 2},
+                {"Name": "CCDTemperature", "Value": -10.5},
+                {"Name": "CoolerPower", "Value": 42.0},
+                {"Name": "ImageReady", "Value": false},
+                {"Name": "IsPulseGuiding", "Value": true},
+                {"Name": "PercentCompleted", "Value": 30},
+                {"Name": "TimeStamp", "Value": "2023-05-06T12:34:56.789Z"}
+            ]"#,
+            CameraDeviceState::from_items,
+        );
+        assert_eq!(
+            state,
+            TimestampedDeviceState {
+                timestamp: timestamp(),
+                state: CameraDeviceState {
+                    camera_state: Some(CameraState::Exposing),
+                    ccd_temperature: Some(-10.5),
+                    cooler_power: Some(42.0),
+                    heat_sink_temperature: None,
+                    image_ready: Some(false),
+                    is_pulse_guiding: Some(true),
+                    percent_completed: Some(30),
+                },
+            }
+        );
+    }
+
+    #[cfg(feature = "switch")]
+    #[test]
+    fn switch_state() {
+        let state = parse(
+            r#"[
+                {"Name": "GetSwitch0", "Value": true},
+                {"Name": "GetSwitchValue0", "Value": 1.0},
+                {"Name": "StateChangeComplete0", "Value": true},
+                {"Name": "GetSwitch12", "Value": false},
+                {"Name": "GetSwitchValue12", "Value": 0.25},
+                {"Name": "TimeStamp", "Value": "2023-05-06T12:34:56.789Z"}
+            ]"#,
+            SwitchDeviceState::from_items,
+        );
+        assert_eq!(state.timestamp, timestamp());
+        assert_eq!(
+            state.state,
+            SwitchDeviceState {
+                get_switch: BTreeMap::from([(0, true), (12, false)]),
+                get_switch_value: BTreeMap::from([(0, 1.0), (12, 0.25)]),
+                state_change_complete: BTreeMap::from([(0, true)]),
+            }
+        );
+    }
+}
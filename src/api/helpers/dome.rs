@@ -0,0 +1,56 @@
+use super::{poll_until, with_deadline, WaitOptions};
+use crate::api::{Dome, ShutterState};
+use crate::{ASCOMError, ASCOMResult};
+
+impl dyn Dome {
+    /// Wait until the dome stops slewing, e.g. after [`Dome::slew_to_azimuth`].
+    ///
+    /// [`Dome::slewing`] is polled every [`WaitOptions::poll_interval`], starting right away,
+    /// so this returns without sleeping if the dome isn't moving. If the deadline is exceeded,
+    /// the dome is stopped via [`Dome::abort_slew`] when [`WaitOptions::abort_on_timeout`] is set.
+    pub async fn wait_while_slewing(&self, wait_options: impl Into<WaitOptions>) -> ASCOMResult {
+        let wait_options = wait_options.into();
+        with_deadline(
+            wait_options,
+            poll_until(wait_options.poll_interval, || async {
+                Ok(!self.slewing().await?)
+            }),
+            || self.abort_slew(),
+        )
+        .await
+    }
+
+    /// Wait until the shutter finishes opening or closing after [`Dome::open_shutter`] or
+    /// [`Dome::close_shutter`].
+    ///
+    /// [`Dome::shutter_status`] is polled every [`WaitOptions::poll_interval`] until it's either
+    /// [`ShutterState::Open`] or [`ShutterState::Closed`], which is then returned. Fails if the
+    /// shutter reports [`ShutterState::Error`]. If the deadline is exceeded, shutter movement
+    /// is stopped via [`Dome::abort_slew`] when [`WaitOptions::abort_on_timeout`] is set.
+    pub async fn wait_for_shutter(
+        &self,
+        wait_options: impl Into<WaitOptions>,
+    ) -> ASCOMResult<ShutterState> {
+        let wait_options = wait_options.into();
+        with_deadline(
+            wait_options,
+            async {
+                loop {
+                    match self.shutter_status().await? {
+                        state @ (ShutterState::Open | ShutterState::Closed) => return Ok(state),
+                        ShutterState::Opening | ShutterState::Closing => {
+                            tokio::time::sleep(wait_options.poll_interval).await;
+                        }
+                        ShutterState::Error => {
+                            return Err(ASCOMError::unspecified(
+                                "shutter entered an error state while moving",
+                            ));
+                        }
+                    }
+                }
+            },
+            || self.abort_slew(),
+        )
+        .await
+    }
+}
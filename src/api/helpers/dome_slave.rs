@@ -0,0 +1,192 @@
+use crate::api::{Dome, Telescope};
+use crate::ASCOMResult;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Dome and mount geometry used to compute where the dome slit should point.
+///
+/// All distances are in the same units, e.g. metres. Offsets describe the position of the
+/// intersection of the mount axes relative to the centre of the dome sphere.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DomeGeometry {
+    /// Radius of the dome.
+    pub radius: f64,
+    /// Offset of the mount towards north.
+    pub mount_offset_north: f64,
+    /// Offset of the mount towards east.
+    pub mount_offset_east: f64,
+    /// Offset of the mount upwards.
+    pub mount_offset_up: f64,
+}
+
+impl DomeGeometry {
+    /// Compute the dome azimuth in degrees at which the telescope pointing at the given
+    /// altitude and azimuth (in degrees) looks through the dome.
+    pub fn dome_azimuth(&self, altitude: f64, azimuth: f64) -> f64 {
+        let (altitude, azimuth) = (altitude.to_radians(), azimuth.to_radians());
+        let direction = [
+            altitude.cos() * azimuth.cos(),
+            altitude.cos() * azimuth.sin(),
+            altitude.sin(),
+        ];
+        let origin = [
+            self.mount_offset_north,
+            self.mount_offset_east,
+            self.mount_offset_up,
+        ];
+
+        // Intersect the ray `origin + t * direction` with the dome sphere.
+        let dot = |a: [f64; 3], b: [f64; 3]| a[0].mul_add(b[0], a[1].mul_add(b[1], a[2] * b[2]));
+        let b = dot(origin, direction);
+        let c = self.radius.mul_add(-self.radius, dot(origin, origin));
+        let t = b.mul_add(b, -c).max(0.0).sqrt() - b;
+
+        let north = t.mul_add(direction[0], origin[0]);
+        let east = t.mul_add(direction[1], origin[1]);
+        east.atan2(north).to_degrees().rem_euclid(360.0)
+    }
+}
+
+/// Keeps a [`Dome`] slit aligned with where a [`Telescope`] is pointing.
+///
+/// This is a client-side alternative to the dome's own slaving for setups where the dome
+/// driver doesn't know about the mount.
+#[derive(Debug)]
+pub struct DomeSlaveController {
+    telescope: Arc<dyn Telescope>,
+    dome: Arc<dyn Dome>,
+    geometry: DomeGeometry,
+    poll_interval: Duration,
+    tolerance: f64,
+}
+
+impl DomeSlaveController {
+    /// Create a controller with a default poll interval of 5 seconds and tolerance of 2 degrees.
+    pub const fn new(
+        telescope: Arc<dyn Telescope>,
+        dome: Arc<dyn Dome>,
+        geometry: DomeGeometry,
+    ) -> Self {
+        Self {
+            telescope,
+            dome,
+            geometry,
+            poll_interval: Duration::from_secs(5),
+            tolerance: 2.0,
+        }
+    }
+
+    /// Set how often to check the telescope position.
+    pub const fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set how far in degrees the dome can lag behind before it's moved.
+    ///
+    /// Larger values reduce wear on the dome motors at the expense of tracking accuracy.
+    pub const fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Move the dome towards the telescope position once if it's outside the tolerance.
+    ///
+    /// The dome isn't commanded while it's still slewing from a previous request.
+    pub async fn sync(&self) -> ASCOMResult {
+        let (altitude, azimuth) =
+            futures::join!(self.telescope.altitude(), self.telescope.azimuth());
+        let target = self.geometry.dome_azimuth(altitude?, azimuth?);
+        let current = self.dome.azimuth().await?;
+
+        let diff = (target - current).rem_euclid(360.0);
+        if diff.min(360.0 - diff) > self.tolerance && !self.dome.slewing().await? {
+            tracing::debug!(current, target, "Slewing dome to follow telescope");
+            self.dome.slew_to_azimuth(target).await?;
+        }
+        Ok(())
+    }
+
+    /// Keep the dome aligned with the telescope every poll interval until an error occurs.
+    pub async fn run(&self) -> ASCOMResult<Infallible> {
+        loop {
+            self.sync().await?;
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Run the controller as a background task.
+    ///
+    /// Must be called within a Tokio runtime. The task stops on the first error or when the
+    /// returned handle is cancelled or dropped.
+    pub fn spawn(self) -> DomeSlaveHandle {
+        DomeSlaveHandle(tokio::spawn(async move { self.run().await }))
+    }
+}
+
+/// Handle to a [`DomeSlaveController`] running in the background.
+///
+/// Dropping the handle stops the controller.
+#[derive(Debug)]
+pub struct DomeSlaveHandle(tokio::task::JoinHandle<ASCOMResult<Infallible>>);
+
+impl DomeSlaveHandle {
+    /// Stop the controller.
+    ///
+    /// A dome slew that's already in progress is not aborted.
+    pub fn cancel(&self) {
+        self.0.abort();
+    }
+
+    /// Wait until the controller stops.
+    ///
+    /// Returns the error that stopped the controller, or `Ok(())` if it was cancelled.
+    pub async fn join(mut self) -> ASCOMResult {
+        match (&mut self.0).await {
+            Ok(result) => result.map(|never| match never {}),
+            Err(err) if err.is_cancelled() => Ok(()),
+            Err(err) => std::panic::resume_unwind(err.into_panic()),
+        }
+    }
+}
+
+impl Drop for DomeSlaveHandle {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DomeGeometry;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn centred_mount_follows_telescope() {
+        let geometry = DomeGeometry {
+            radius: 2.0,
+            ..DomeGeometry::default()
+        };
+        for azimuth in [0.0, 45.0, 180.0, 300.0] {
+            assert_close(geometry.dome_azimuth(30.0, azimuth), azimuth);
+        }
+    }
+
+    #[test]
+    fn offset_mount() {
+        let geometry = DomeGeometry {
+            radius: 2.0,
+            mount_offset_east: 1.0,
+            ..DomeGeometry::default()
+        };
+        // Pointing north at the horizon from 1 unit east of the centre hits the dome
+        // at sqrt(3) units north, i.e. 30 degrees east of north.
+        assert_close(geometry.dome_azimuth(0.0, 0.0), 30.0);
+        // Pointing at the zenith hits the dome straight above the mount.
+        assert_close(geometry.dome_azimuth(90.0, 0.0), 90.0);
+    }
+}
@@ -0,0 +1,96 @@
+use super::{poll_until, with_deadline, WaitOptions};
+use crate::api::Focuser;
+use crate::{ASCOMError, ASCOMResult};
+use std::time::Duration;
+
+/// Direction from which the focuser should arrive at the target position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApproachDirection {
+    /// Finish the move with increasing step positions.
+    Outward,
+    /// Finish the move with decreasing step positions.
+    Inward,
+}
+
+impl dyn Focuser {
+    /// Move an absolute focuser to the given position and wait until it stops,
+    /// splitting the move into chunks of at most [`Focuser::max_increment`] steps.
+    async fn move_and_wait(&self, target: i32, poll_interval: Duration) -> ASCOMResult {
+        let max_increment = self.max_increment().await?.max(1);
+        let mut position = self.position().await?;
+
+        while position != target {
+            position = if target > position {
+                target.min(position.saturating_add(max_increment))
+            } else {
+                target.max(position.saturating_sub(max_increment))
+            };
+            self.move_(position).await?;
+            poll_until(poll_interval, || async { Ok(!self.is_moving().await?) }).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Move to the given position always arriving from the same direction.
+    ///
+    /// This compensates for mechanical backlash on focusers that don't handle it in hardware:
+    /// if the final approach would otherwise happen from the wrong side, the focuser first
+    /// overshoots the target by `backlash_steps` (clamped to `0..=max_step`) and then moves back.
+    /// Negative `backlash_steps` are rejected with an `INVALID_VALUE` error.
+    ///
+    /// Moves are split according to [`Focuser::max_increment`], and completion of each move is
+    /// detected by polling [`Focuser::is_moving`] every [`WaitOptions::poll_interval`].
+    /// [`WaitOptions::deadline`] applies to the whole sequence of moves, and the focuser is halted
+    /// on timeout if [`WaitOptions::abort_on_timeout`] is set.
+    ///
+    /// Only absolute focusers are supported.
+    pub async fn move_with_backlash(
+        &self,
+        target: i32,
+        backlash_steps: i32,
+        approach_direction: ApproachDirection,
+        wait_options: impl Into<WaitOptions>,
+    ) -> ASCOMResult {
+        let wait_options = wait_options.into();
+        if backlash_steps < 0 {
+            return Err(ASCOMError::invalid_value(format_args!(
+                "backlash steps must not be negative, got {backlash_steps}"
+            )));
+        }
+        if !self.absolute().await? {
+            return Err(ASCOMError::invalid_operation(
+                "backlash compensation requires an absolute focuser",
+            ));
+        }
+
+        let max_step = self.max_step().await?;
+        if !(0..=max_step).contains(&target) {
+            return Err(ASCOMError::invalid_value(format_args!(
+                "target position {target} is out of range 0..={max_step}"
+            )));
+        }
+
+        let position = self.position().await?;
+        let needs_overshoot = match approach_direction {
+            ApproachDirection::Outward => position >= target,
+            ApproachDirection::Inward => position <= target,
+        };
+
+        let poll_interval = wait_options.poll_interval;
+        let moves = async {
+            if needs_overshoot && position != target {
+                let overshoot = match approach_direction {
+                    ApproachDirection::Outward => target.saturating_sub(backlash_steps).max(0),
+                    ApproachDirection::Inward => {
+                        target.saturating_add(backlash_steps).min(max_step)
+                    }
+                };
+                self.move_and_wait(overshoot, poll_interval).await?;
+            }
+            self.move_and_wait(target, poll_interval).await
+        };
+
+        with_deadline(wait_options, moves, || self.halt()).await
+    }
+}
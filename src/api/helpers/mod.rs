@@ -0,0 +1,178 @@
+//! Client-side convenience helpers built on top of the device traits.
+//!
+//! These are implemented as inherent methods on the trait objects (e.g. `dyn Switch`)
+//! so that they're available on any device returned by [`Client`](crate::Client),
+//! without polluting the traits that driver authors need to implement.
+
+mod device;
+pub use device::{ConnectedGuard, DeviceExt};
+
+#[cfg(feature = "__anydevice")]
+mod device_state;
+#[cfg(feature = "__anydevice")]
+pub use device_state::*;
+
+#[cfg(feature = "camera")]
+mod camera;
+#[cfg(feature = "camera")]
+pub use camera::{CameraStatus, FrameMetadata, ReadoutConfig};
+
+#[cfg(feature = "dome")]
+mod dome;
+
+#[cfg(all(feature = "dome", feature = "telescope"))]
+mod dome_slave;
+#[cfg(all(feature = "dome", feature = "telescope"))]
+pub use dome_slave::{DomeGeometry, DomeSlaveController, DomeSlaveHandle};
+
+#[cfg(feature = "focuser")]
+mod focuser;
+#[cfg(feature = "focuser")]
+pub use focuser::ApproachDirection;
+
+#[cfg(feature = "observingconditions")]
+mod observingconditions;
+#[cfg(feature = "observingconditions")]
+pub use observingconditions::{ObservingConditionsSnapshot, SensorName};
+
+#[cfg(feature = "safetymonitor")]
+mod safetymonitor;
+#[cfg(feature = "safetymonitor")]
+pub use safetymonitor::UnsafeActions;
+
+#[cfg(feature = "switch")]
+mod switch;
+
+#[cfg(feature = "telescope")]
+mod astrometry;
+#[cfg(feature = "telescope")]
+mod telescope;
+#[cfg(feature = "telescope")]
+pub use telescope::TargetVisibility;
+
+use crate::{ASCOMError, ASCOMErrorCode, ASCOMResult};
+use std::future::Future;
+use std::time::Duration;
+
+/// Polling and timeout settings for helpers that wait for a device operation to complete.
+///
+/// A plain [`Duration`] converts into options with that poll interval and no deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WaitOptions {
+    /// Pause between consecutive status requests.
+    pub poll_interval: Duration,
+    /// Maximum total time to wait for the operation to complete, or `None` to wait indefinitely.
+    ///
+    /// This guards against devices that get stuck reporting an in-progress state.
+    pub deadline: Option<Duration>,
+    /// Whether to abort the operation (e.g. halt the focuser) when the deadline is exceeded.
+    ///
+    /// Ignored by helpers for operations that can't be aborted.
+    pub abort_on_timeout: bool,
+}
+
+impl WaitOptions {
+    /// Create options with the given poll interval and no deadline.
+    pub const fn new(poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            deadline: None,
+            abort_on_timeout: false,
+        }
+    }
+
+    /// Give up with an error if the operation doesn't complete within `deadline`.
+    pub const fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Abort the operation on the device if the deadline is exceeded.
+    pub const fn with_abort_on_timeout(mut self, abort_on_timeout: bool) -> Self {
+        self.abort_on_timeout = abort_on_timeout;
+        self
+    }
+}
+
+impl From<Duration> for WaitOptions {
+    fn from(poll_interval: Duration) -> Self {
+        Self::new(poll_interval)
+    }
+}
+
+/// Repeatedly calls `is_complete` with `poll_interval` pauses in between until it returns `true`.
+pub(crate) async fn poll_until<Fut: Future<Output = ASCOMResult<bool>>>(
+    poll_interval: Duration,
+    mut is_complete: impl FnMut() -> Fut,
+) -> ASCOMResult {
+    while !is_complete().await? {
+        tokio::time::sleep(poll_interval).await;
+    }
+    Ok(())
+}
+
+/// Runs `operation` to completion, or gives up after [`WaitOptions::deadline`] with an error,
+/// calling `abort` first if [`WaitOptions::abort_on_timeout`] is set.
+#[cfg_attr(
+    not(any(
+        feature = "camera",
+        feature = "dome",
+        feature = "focuser",
+        feature = "switch",
+        feature = "telescope"
+    )),
+    allow(dead_code)
+)]
+pub(crate) async fn with_deadline<T, AbortFut: Future<Output = ASCOMResult>>(
+    options: WaitOptions,
+    operation: impl Future<Output = ASCOMResult<T>>,
+    abort: impl FnOnce() -> AbortFut,
+) -> ASCOMResult<T> {
+    let Some(deadline) = options.deadline else {
+        return operation.await;
+    };
+    if let Ok(result) = tokio::time::timeout(deadline, operation).await {
+        return result;
+    }
+    if options.abort_on_timeout {
+        if let Err(err) = abort().await {
+            tracing::error!(%err, "Failed to abort timed out operation");
+        }
+    }
+    Err(ASCOMError::unspecified(format_args!(
+        "operation didn't complete within {deadline:?}"
+    )))
+}
+
+/// Turns `NOT_IMPLEMENTED` errors into `None`, keeping other errors as-is.
+#[cfg_attr(
+    not(any(
+        feature = "camera",
+        feature = "observingconditions",
+        feature = "telescope"
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn if_implemented<T>(result: ASCOMResult<T>) -> ASCOMResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.code == ASCOMErrorCode::NOT_IMPLEMENTED => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Logs all errors from a batch of independent operations and returns the first one, if any.
+#[cfg_attr(
+    not(any(feature = "safetymonitor", feature = "telescope")),
+    allow(dead_code)
+)]
+pub(crate) fn first_error(results: impl IntoIterator<Item = ASCOMResult>) -> ASCOMResult {
+    let mut first_error = Ok(());
+    for err in results.into_iter().filter_map(Result::err) {
+        tracing::error!(%err, "Operation failed");
+        if first_error.is_ok() {
+            first_error = Err(err);
+        }
+    }
+    first_error
+}
@@ -0,0 +1,180 @@
+use super::if_implemented;
+use crate::api::ObservingConditions;
+use crate::ASCOMResult;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+/// Sensors defined by the `ObservingConditions` interface.
+///
+/// Converts into the `SensorName` string expected by [`ObservingConditions::sensor_description`]
+/// and [`ObservingConditions::time_since_last_update`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(missing_docs)]
+pub enum SensorName {
+    CloudCover,
+    DewPoint,
+    Humidity,
+    Pressure,
+    RainRate,
+    SkyBrightness,
+    SkyQuality,
+    SkyTemperature,
+    StarFWHM,
+    Temperature,
+    WindDirection,
+    WindGust,
+    WindSpeed,
+}
+
+impl SensorName {
+    /// All the sensors defined by the interface.
+    pub const ALL: [Self; 13] = [
+        Self::CloudCover,
+        Self::DewPoint,
+        Self::Humidity,
+        Self::Pressure,
+        Self::RainRate,
+        Self::SkyBrightness,
+        Self::SkyQuality,
+        Self::SkyTemperature,
+        Self::StarFWHM,
+        Self::Temperature,
+        Self::WindDirection,
+        Self::WindGust,
+        Self::WindSpeed,
+    ];
+
+    /// Sensor name as used in the Alpaca API, e.g. `"SkyQuality"`.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::CloudCover => "CloudCover",
+            Self::DewPoint => "DewPoint",
+            Self::Humidity => "Humidity",
+            Self::Pressure => "Pressure",
+            Self::RainRate => "RainRate",
+            Self::SkyBrightness => "SkyBrightness",
+            Self::SkyQuality => "SkyQuality",
+            Self::SkyTemperature => "SkyTemperature",
+            Self::StarFWHM => "StarFWHM",
+            Self::Temperature => "Temperature",
+            Self::WindDirection => "WindDirection",
+            Self::WindGust => "WindGust",
+            Self::WindSpeed => "WindSpeed",
+        }
+    }
+}
+
+impl Display for SensorName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<SensorName> for String {
+    fn from(sensor: SensorName) -> Self {
+        sensor.as_str().to_owned()
+    }
+}
+
+/// Readings of all the sensors of an [`ObservingConditions`] device.
+///
+/// Returned by the `read_all` helper on [`dyn ObservingConditions`](ObservingConditions).
+/// Sensors the device doesn't implement are left as `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ObservingConditionsSnapshot {
+    /// Percentage of the sky obscured by cloud.
+    pub cloud_cover: Option<f64>,
+    /// Atmospheric dew point in degrees Celsius.
+    pub dew_point: Option<f64>,
+    /// Atmospheric relative humidity in percent.
+    pub humidity: Option<f64>,
+    /// Atmospheric pressure in hectopascals at the observatory's altitude.
+    pub pressure: Option<f64>,
+    /// Rain rate in millimetres per hour.
+    pub rain_rate: Option<f64>,
+    /// Sky brightness in lux.
+    pub sky_brightness: Option<f64>,
+    /// Sky quality in magnitudes per square arc-second.
+    pub sky_quality: Option<f64>,
+    /// Sky temperature in degrees Celsius.
+    pub sky_temperature: Option<f64>,
+    /// Seeing as the FWHM of a star in arc-seconds.
+    pub star_fwhm: Option<f64>,
+    /// Ambient temperature in degrees Celsius.
+    pub temperature: Option<f64>,
+    /// Wind direction in degrees, with 0 meaning north.
+    pub wind_direction: Option<f64>,
+    /// Peak wind gust over the last 2 minutes in metres per second.
+    pub wind_gust: Option<f64>,
+    /// Wind speed in metres per second.
+    pub wind_speed: Option<f64>,
+}
+
+impl dyn ObservingConditions {
+    /// Check which sensors are implemented by the device.
+    ///
+    /// Returns a map from sensor name (as used by [`ObservingConditions::sensor_description`],
+    /// e.g. `"SkyQuality"`) to whether the sensor is available. Sensors are probed concurrently
+    /// via `sensor_description`, which devices are required to fail with `NOT_IMPLEMENTED`
+    /// for unsupported sensors.
+    pub async fn sensor_availability(&self) -> ASCOMResult<HashMap<&'static str, bool>> {
+        futures::future::join_all(SensorName::ALL.map(|sensor| async move {
+            let description = self.sensor_description(sensor.into()).await;
+            Ok((sensor.as_str(), if_implemented(description)?.is_some()))
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+
+    /// Read all the sensors in one go.
+    ///
+    /// All properties are requested concurrently, which saves a lot of round-trips compared
+    /// to reading them one by one when polling for a dashboard.
+    pub async fn read_all(&self) -> ASCOMResult<ObservingConditionsSnapshot> {
+        let (
+            cloud_cover,
+            dew_point,
+            humidity,
+            pressure,
+            rain_rate,
+            sky_brightness,
+            sky_quality,
+            sky_temperature,
+            star_fwhm,
+            temperature,
+            wind_direction,
+            wind_gust,
+            wind_speed,
+        ) = futures::join!(
+            self.cloud_cover(),
+            self.dew_point(),
+            self.humidity(),
+            self.pressure(),
+            self.rain_rate(),
+            self.sky_brightness(),
+            self.sky_quality(),
+            self.sky_temperature(),
+            self.star_fwhm(),
+            self.temperature(),
+            self.wind_direction(),
+            self.wind_gust(),
+            self.wind_speed(),
+        );
+        Ok(ObservingConditionsSnapshot {
+            cloud_cover: if_implemented(cloud_cover)?,
+            dew_point: if_implemented(dew_point)?,
+            humidity: if_implemented(humidity)?,
+            pressure: if_implemented(pressure)?,
+            rain_rate: if_implemented(rain_rate)?,
+            sky_brightness: if_implemented(sky_brightness)?,
+            sky_quality: if_implemented(sky_quality)?,
+            sky_temperature: if_implemented(sky_temperature)?,
+            star_fwhm: if_implemented(star_fwhm)?,
+            temperature: if_implemented(temperature)?,
+            wind_direction: if_implemented(wind_direction)?,
+            wind_gust: if_implemented(wind_gust)?,
+            wind_speed: if_implemented(wind_speed)?,
+        })
+    }
+}
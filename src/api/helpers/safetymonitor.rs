@@ -0,0 +1,99 @@
+use super::{first_error, poll_until};
+#[cfg(feature = "camera")]
+use crate::api::Camera;
+use crate::api::SafetyMonitor;
+#[cfg(feature = "telescope")]
+use crate::api::Telescope;
+use crate::ASCOMResult;
+#[cfg(any(feature = "camera", feature = "telescope"))]
+use crate::ASCOMResultExt;
+#[cfg(any(feature = "camera", feature = "telescope"))]
+use futures::future::join_all;
+#[cfg(any(feature = "camera", feature = "telescope"))]
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Actions to perform once a [`SafetyMonitor`] reports unsafe conditions.
+///
+/// Used by the `run_watchdog` helper on [`dyn SafetyMonitor`](SafetyMonitor).
+#[derive(Debug, Clone, Default)]
+pub struct UnsafeActions {
+    /// Cameras whose current exposures should be aborted.
+    #[cfg(feature = "camera")]
+    pub cameras: Vec<Arc<dyn Camera>>,
+    /// Telescopes that should stop tracking.
+    #[cfg(feature = "telescope")]
+    pub telescopes: Vec<Arc<dyn Telescope>>,
+    /// Whether to park the telescopes instead of just stopping tracking.
+    #[cfg(feature = "telescope")]
+    pub park_telescopes: bool,
+}
+
+impl UnsafeActions {
+    /// Perform all the actions.
+    ///
+    /// Exposures are aborted first, then telescopes are stopped or parked. Devices within each
+    /// group are handled concurrently, and a failure on one device doesn't prevent actions on
+    /// the others. All failures are logged and the first one is returned.
+    pub async fn run(&self) -> ASCOMResult {
+        let results = std::iter::empty::<ASCOMResult>();
+
+        #[cfg(feature = "camera")]
+        let results = results.chain(
+            join_all(self.cameras.iter().map(|camera| async move {
+                camera
+                    .abort_exposure()
+                    .await
+                    .context("failed to abort camera exposure")
+            }))
+            .await,
+        );
+
+        #[cfg(feature = "telescope")]
+        let results = results.chain(
+            join_all(self.telescopes.iter().map(|telescope| async move {
+                if self.park_telescopes {
+                    telescope.park().await.context("failed to park telescope")
+                } else {
+                    telescope
+                        .set_tracking(false)
+                        .await
+                        .context("failed to stop telescope tracking")
+                }
+            }))
+            .await,
+        );
+
+        first_error(results)
+    }
+}
+
+impl dyn SafetyMonitor {
+    /// Poll [`SafetyMonitor::is_safe`] every `poll_interval` and perform the given actions
+    /// as soon as conditions become unsafe.
+    ///
+    /// Errors while reading the safety state are logged and treated as unsafe, since a monitor
+    /// that can't be reached shouldn't keep the equipment exposed.
+    ///
+    /// Returns once the actions have been performed; see [`UnsafeActions::run`] for the result.
+    /// This is meant to be spawned as a background task for the duration of an imaging session.
+    pub async fn run_watchdog(
+        &self,
+        poll_interval: Duration,
+        actions: &UnsafeActions,
+    ) -> ASCOMResult {
+        poll_until(poll_interval, || async {
+            Ok(match self.is_safe().await {
+                Ok(is_safe) => !is_safe,
+                Err(err) => {
+                    tracing::warn!(%err, "Failed to read safety state, treating as unsafe");
+                    true
+                }
+            })
+        })
+        .await?;
+
+        tracing::warn!("Safety monitor reported unsafe conditions, performing safety actions");
+        actions.run().await
+    }
+}
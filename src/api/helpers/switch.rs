@@ -0,0 +1,60 @@
+use super::{poll_until, with_deadline, WaitOptions};
+use crate::api::Switch;
+use crate::ASCOMResult;
+
+impl dyn Switch {
+    /// Set the given switch to a boolean state and wait until the change is complete.
+    ///
+    /// If the switch supports asynchronous operation (as reported by [`Switch::can_async`]),
+    /// this issues [`Switch::set_async`] and then polls [`Switch::state_change_complete`]
+    /// every [`WaitOptions::poll_interval`] until it reports completion, failing if that
+    /// takes longer than [`WaitOptions::deadline`]. Switches can't abort an asynchronous
+    /// change, so [`WaitOptions::abort_on_timeout`] has no effect.
+    ///
+    /// Otherwise it falls back to the synchronous [`Switch::set_switch`].
+    pub async fn set_async_and_wait(
+        &self,
+        id: i32,
+        state: bool,
+        wait_options: impl Into<WaitOptions>,
+    ) -> ASCOMResult {
+        if !self.can_async(id).await? {
+            return self.set_switch(id, state).await;
+        }
+        let wait_options = wait_options.into();
+        self.set_async(id, state).await?;
+        with_deadline(
+            wait_options,
+            poll_until(wait_options.poll_interval, || {
+                self.state_change_complete(id)
+            }),
+            || async { Ok(()) },
+        )
+        .await
+    }
+
+    /// Set the given switch to a numeric value and wait until the change is complete.
+    ///
+    /// This is the same as [`set_async_and_wait`](Self::set_async_and_wait), but uses
+    /// [`Switch::set_async_value`] and falls back to [`Switch::set_switch_value`].
+    pub async fn set_async_value_and_wait(
+        &self,
+        id: i32,
+        value: f64,
+        wait_options: impl Into<WaitOptions>,
+    ) -> ASCOMResult {
+        if !self.can_async(id).await? {
+            return self.set_switch_value(id, value).await;
+        }
+        let wait_options = wait_options.into();
+        self.set_async_value(id, value).await?;
+        with_deadline(
+            wait_options,
+            poll_until(wait_options.poll_interval, || {
+                self.state_change_complete(id)
+            }),
+            || async { Ok(()) },
+        )
+        .await
+    }
+}
@@ -0,0 +1,203 @@
+use super::{astrometry, first_error, if_implemented, poll_until, with_deadline, WaitOptions};
+use crate::api::{EquatorialCoordinateType, Telescope, TelescopeAxis};
+use crate::{ASCOMError, ASCOMErrorCode, ASCOMResult, ASCOMResultExt};
+use std::time::{Duration, SystemTime};
+
+/// Position of a target relative to the local horizon and meridian.
+///
+/// Returned by the `target_visibility` helper on [`dyn Telescope`](Telescope).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetVisibility {
+    /// Hour angle of the target in hours, normalized to `-12.0..12.0`.
+    ///
+    /// Negative values are east of the meridian (rising), positive values are west (setting).
+    pub hour_angle: f64,
+    /// Geometric altitude of the target above the horizon in degrees.
+    pub altitude: f64,
+    /// Whether the altitude is at or above the requested minimum.
+    pub is_above_min_altitude: bool,
+}
+
+impl TargetVisibility {
+    fn compute(
+        sidereal_time: f64,
+        latitude: f64,
+        right_ascension: f64,
+        declination: f64,
+        min_altitude: f64,
+    ) -> Self {
+        let hour_angle = (sidereal_time - right_ascension + 12.0).rem_euclid(24.0) - 12.0;
+
+        let latitude = latitude.to_radians();
+        let declination = declination.to_radians();
+        let altitude = declination
+            .sin()
+            .mul_add(
+                latitude.sin(),
+                declination.cos() * latitude.cos() * (hour_angle * 15.0).to_radians().cos(),
+            )
+            .clamp(-1.0, 1.0)
+            .asin()
+            .to_degrees();
+
+        Self {
+            hour_angle,
+            altitude,
+            is_above_min_altitude: altitude >= min_altitude,
+        }
+    }
+}
+
+impl dyn Telescope {
+    /// Wait until the mount stops slewing, e.g. after [`Telescope::slew_to_coordinates_async`].
+    ///
+    /// [`Telescope::slewing`] is polled every [`WaitOptions::poll_interval`], starting right away,
+    /// so this returns without sleeping if the mount isn't moving. If the deadline is exceeded,
+    /// the mount is stopped via [`Telescope::abort_slew`] when [`WaitOptions::abort_on_timeout`] is set.
+    pub async fn wait_while_slewing(&self, wait_options: impl Into<WaitOptions>) -> ASCOMResult {
+        let wait_options = wait_options.into();
+        with_deadline(
+            wait_options,
+            poll_until(wait_options.poll_interval, || async {
+                Ok(!self.slewing().await?)
+            }),
+            || self.abort_slew(),
+        )
+        .await
+    }
+
+    /// Slew to the given coordinates and wait until the mount stops slewing.
+    ///
+    /// This starts the slew via [`Telescope::slew_to_coordinates_async`], then waits for
+    /// `settle_delay` before polling, as some mounts briefly report they aren't slewing right
+    /// after accepting the command. The slew fails with [`ASCOMErrorCode::INVALID_WHILE_PARKED`]
+    /// if the mount reports being parked while waiting, which indicates a fault.
+    ///
+    /// Deadline and polling behave like in [`wait_while_slewing`](Self::wait_while_slewing),
+    /// with `settle_delay` counted towards the deadline.
+    pub async fn slew_to_coordinates_and_wait(
+        &self,
+        right_ascension: f64,
+        declination: f64,
+        settle_delay: Duration,
+        wait_options: impl Into<WaitOptions>,
+    ) -> ASCOMResult {
+        let wait_options = wait_options.into();
+        self.slew_to_coordinates_async(right_ascension, declination)
+            .await?;
+        with_deadline(
+            wait_options,
+            async {
+                tokio::time::sleep(settle_delay).await;
+                poll_until(wait_options.poll_interval, || async {
+                    if self.at_park().await? {
+                        return Err(ASCOMError::new(
+                            ASCOMErrorCode::INVALID_WHILE_PARKED,
+                            "mount was parked before the slew completed",
+                        ));
+                    }
+                    Ok(!self.slewing().await?)
+                })
+                .await
+            },
+            || self.abort_slew(),
+        )
+        .await
+    }
+
+    /// Compute the hour angle and altitude of the given target for the telescope's site and current time.
+    ///
+    /// `right_ascension` is in hours and `declination` in degrees, both in the same
+    /// equatorial system as the mount reports. The calculation uses the mount's
+    /// [`sidereal_time`](Telescope::sidereal_time) and [`site_latitude`](Telescope::site_latitude)
+    /// and ignores refraction, so it's meant for planning rather than precise pointing.
+    pub async fn target_visibility(
+        &self,
+        right_ascension: f64,
+        declination: f64,
+        min_altitude: f64,
+    ) -> ASCOMResult<TargetVisibility> {
+        Ok(TargetVisibility::compute(
+            self.sidereal_time().await?,
+            self.site_latitude().await?,
+            right_ascension,
+            declination,
+            min_altitude,
+        ))
+    }
+
+    /// Convert coordinates from the given frame into the mount's [`equatorial_system`](Telescope::equatorial_system).
+    ///
+    /// Mounts that don't report their system are assumed to use topocentric (JNow) coordinates,
+    /// and the mount's [`utc_date`](Telescope::utc_date) (or local time if unavailable) is used
+    /// as the date for precession and nutation.
+    ///
+    /// The conversion is approximate: aberration, proper motion and refraction are not applied,
+    /// which results in errors of up to ~20 arcseconds. Conversions to or from B1950 and custom
+    /// frames are not supported.
+    pub async fn to_mount_coordinates(
+        &self,
+        right_ascension: f64,
+        declination: f64,
+        frame: EquatorialCoordinateType,
+    ) -> ASCOMResult<(f64, f64)> {
+        let mount_frame = if_implemented(self.equatorial_system().await)?
+            .unwrap_or(EquatorialCoordinateType::Topocentric);
+        if mount_frame == frame {
+            return Ok((right_ascension, declination));
+        }
+        let time = if_implemented(self.utc_date().await)?.unwrap_or_else(SystemTime::now);
+        astrometry::convert((right_ascension, declination), frame, mount_frame, time)
+    }
+
+    /// Start slewing to coordinates given in the specified frame, converting them to the mount's
+    /// equatorial system first via [`to_mount_coordinates`](Self::to_mount_coordinates).
+    ///
+    /// Like [`Telescope::slew_to_coordinates_async`], this returns as soon as the slew starts.
+    pub async fn slew_to_coordinates_from(
+        &self,
+        right_ascension: f64,
+        declination: f64,
+        frame: EquatorialCoordinateType,
+    ) -> ASCOMResult {
+        let (right_ascension, declination) = self
+            .to_mount_coordinates(right_ascension, declination, frame)
+            .await?;
+        self.slew_to_coordinates_async(right_ascension, declination)
+            .await
+    }
+
+    /// Stop all telescope motion as quickly as possible.
+    ///
+    /// This concurrently aborts any slew in progress and sets the rate of every movable axis to zero,
+    /// and optionally turns tracking off as well. All commands are issued even if some of them fail;
+    /// failures are logged and the first one is returned.
+    pub async fn emergency_stop(&self, stop_tracking: bool) -> ASCOMResult {
+        let stop_axis = |axis| async move {
+            if self.can_move_axis(axis).await? {
+                self.move_axis(axis, 0.0)
+                    .await
+                    .context(format_args!("failed to stop {axis:?} axis"))?;
+            }
+            Ok(())
+        };
+
+        let (abort_slew, primary, secondary, tertiary, tracking) = futures::join!(
+            async { self.abort_slew().await.context("failed to abort slew") },
+            stop_axis(TelescopeAxis::Primary),
+            stop_axis(TelescopeAxis::Secondary),
+            stop_axis(TelescopeAxis::Tertiary),
+            async {
+                if stop_tracking {
+                    self.set_tracking(false)
+                        .await
+                        .context("failed to stop tracking")
+                } else {
+                    Ok(())
+                }
+            },
+        );
+
+        first_error([abort_slew, primary, secondary, tertiary, tracking])
+    }
+}
@@ -3,11 +3,13 @@ use super::{
     COLOUR_AXIS, IMAGE_BYTES_TYPE,
 };
 use crate::api::TransmissionElementType;
-use crate::client::{Response, ResponseTransaction, ResponseWithTransaction};
+use crate::client::{
+    DecodeOptions, Response, ResponseTransaction, ResponseWithTransaction, StreamDecoder,
+};
 use crate::{ASCOMError, ASCOMErrorCode, ASCOMResult};
-use bytemuck::PodCastError;
+use bytemuck::{Pod, PodCastError, Zeroable};
 use mime::Mime;
-use ndarray::{Array2, Array3};
+use ndarray::{Array, Array2, Array3, Dimension};
 use num_enum::TryFromPrimitive;
 use serde::de::{DeserializeOwned, IgnoredAny, Visitor};
 use serde::Deserialize;
@@ -40,6 +42,58 @@ fn expect_key<'de, A: serde::de::MapAccess<'de>>(
     }
 }
 
+/// Pixel value deserialized straight from a JSON number with an explicit range check.
+///
+/// This avoids the generic `i32` error message and lets us report the offending value.
+#[derive(Clone, Copy, Zeroable, Pod)]
+#[repr(transparent)]
+struct Pixel(i32);
+
+fn checked_pixel<E: serde::de::Error>(
+    value: impl Copy + std::fmt::Display + TryInto<i32>,
+) -> Result<Pixel, E> {
+    value.try_into().ok().map(Pixel).ok_or_else(|| {
+        E::custom(format_args!(
+            "pixel value {value} is out of the 32-bit integer range"
+        ))
+    })
+}
+
+impl<'de> Deserialize<'de> for Pixel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PixelVisitor;
+
+        impl Visitor<'_> for PixelVisitor {
+            type Value = Pixel;
+
+            fn expecting(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                fmt.write_str("an integer pixel value")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, value: i64) -> Result<Pixel, E> {
+                checked_pixel(value)
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, value: u64) -> Result<Pixel, E> {
+                checked_pixel(value)
+            }
+        }
+
+        deserializer.deserialize_i32(PixelVisitor)
+    }
+}
+
+/// Reinterpret deserialized pixels as plain `i32` values, without copying in the common case.
+fn into_i32_array<D: Dimension>(array: Array<Pixel, D>) -> Array<i32, D> {
+    if !array.is_standard_layout() {
+        return array.mapv(|Pixel(value)| value);
+    }
+    let dim = array.raw_dim();
+    let (pixels, _offset) = array.into_raw_vec_and_offset();
+    Array::from_shape_vec(dim, bytemuck::cast_vec(pixels))
+        .expect("standard layout array should match its own shape")
+}
+
 #[derive(Deserialize)]
 #[serde(transparent)]
 struct ResponseData<A>(#[serde(with = "serde_ndim")] A)
@@ -65,11 +119,13 @@ impl<'de> Visitor<'de> for ResponseVisitor {
 
         expect_key(&mut map, KnownKey::Value)?;
         let data = match rank {
-            ImageArrayRank::Rank2 => map
-                .next_value::<ResponseData<Array2<i32>>>()?
-                .0
-                .insert_axis(COLOUR_AXIS),
-            ImageArrayRank::Rank3 => map.next_value::<ResponseData<Array3<i32>>>()?.0,
+            ImageArrayRank::Rank2 => {
+                into_i32_array(map.next_value::<ResponseData<Array2<Pixel>>>()?.0)
+                    .insert_axis(COLOUR_AXIS)
+            }
+            ImageArrayRank::Rank3 => {
+                into_i32_array(map.next_value::<ResponseData<Array3<Pixel>>>()?.0)
+            }
         };
 
         // Consume leftover fields.
@@ -95,28 +151,40 @@ fn cast_raw_data<T: AsTransmissionElementType>(data: &[u8]) -> Result<Vec<i32>,
         .collect())
 }
 
-impl Response for ASCOMResult<ImageArray> {
-    fn prepare_reqwest(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
-        request.header(reqwest::header::ACCEPT, IMAGE_BYTES_TYPE)
+/// Append values from unaligned little-endian data that contains only whole elements.
+fn extend_unaligned<T: AsTransmissionElementType>(data: &mut Vec<i32>, bytes: &[u8]) {
+    data.extend(
+        bytes
+            .chunks_exact(size_of::<T>())
+            .map(|element| bytemuck::pod_read_unaligned::<T>(element).into()),
+    );
+}
+
+impl TransmissionElementType {
+    const fn size(self) -> usize {
+        match self {
+            Self::I16 => size_of::<i16>(),
+            Self::I32 => size_of::<i32>(),
+            Self::U8 => size_of::<u8>(),
+            Self::U16 => size_of::<u16>(),
+        }
     }
+}
 
-    fn from_reqwest(mime_type: Mime, bytes: &[u8]) -> eyre::Result<ResponseWithTransaction<Self>> {
-        if mime_type.essence_str() != IMAGE_BYTES_TYPE {
-            let transaction = ResponseTransaction::from_reqwest(mime_type, bytes)?;
-            let ascom_error = serde_json::from_slice::<ASCOMError>(bytes)?;
+/// Information from the ImageBytes metadata validated before reading the rest of the body.
+struct ImageBytesHeader {
+    transaction: ResponseTransaction,
+    data_start: usize,
+    /// Shape and element type of the image, or the error code for error responses.
+    content: Result<(ndarray::Ix3, TransmissionElementType), ASCOMErrorCode>,
+}
 
-            return Ok(ResponseWithTransaction {
-                transaction,
-                response: match ascom_error.code {
-                    ASCOMErrorCode::OK => Ok(serde_json::from_slice::<JsonImageArray>(bytes)?.0),
-                    _ => Err(ascom_error),
-                },
-            });
-        }
+impl ImageBytesHeader {
+    fn parse(bytes: &[u8], max_image_pixels: usize) -> eyre::Result<Self> {
         let metadata = bytes
             .get(..size_of::<ImageBytesMetadata>())
             .ok_or_else(|| eyre::eyre!("not enough bytes to read image metadata"))?;
-        let metadata = bytemuck::try_from_bytes::<ImageBytesMetadata>(metadata)?;
+        let metadata = bytemuck::pod_read_unaligned::<ImageBytesMetadata>(metadata);
         eyre::ensure!(
             metadata.metadata_version == 1_i32,
             "unsupported metadata version {}",
@@ -127,49 +195,315 @@ impl Response for ASCOMResult<ImageArray> {
             data_start >= size_of::<ImageBytesMetadata>(),
             "image data start offset is within metadata",
         );
-        let raw_data = bytes
-            .get(data_start..)
-            .ok_or_else(|| eyre::eyre!("image data start offset is out of bounds"))?;
         let transaction = ResponseTransaction {
             client_transaction_id: metadata.client_transaction_id,
             server_transaction_id: metadata.server_transaction_id,
         };
-        let ascom_result = if metadata.error_number == 0_i32 {
-            let ImageElementType::I32 =
-                ImageElementType::try_from_primitive(metadata.image_element_type)?;
-            let transmission_element_type =
-                TransmissionElementType::try_from_primitive(metadata.transmission_element_type)?;
-            let data = match transmission_element_type {
-                TransmissionElementType::I16 => cast_raw_data::<i16>(raw_data),
-                TransmissionElementType::I32 => cast_raw_data::<i32>(raw_data),
-                TransmissionElementType::U8 => cast_raw_data::<u8>(raw_data),
-                TransmissionElementType::U16 => cast_raw_data::<u16>(raw_data),
-            }?;
-            let shape = ndarray::Ix3(
-                usize::try_from(metadata.dimension_1)?,
-                usize::try_from(metadata.dimension_2)?,
-                match ImageArrayRank::try_from_primitive(metadata.rank)? {
-                    ImageArrayRank::Rank2 => {
-                        eyre::ensure!(
-                            metadata.dimension_3 == 0_i32,
-                            "dimension 3 must be 0 for rank 2, got {}",
-                            metadata.dimension_3,
-                        );
-                        1
-                    }
-                    ImageArrayRank::Rank3 => usize::try_from(metadata.dimension_3)?,
+        if metadata.error_number != 0_i32 {
+            return Ok(Self {
+                transaction,
+                data_start,
+                content: Err(ASCOMErrorCode::try_from(u16::try_from(
+                    metadata.error_number,
+                )?)?),
+            });
+        }
+        let ImageElementType::I32 =
+            ImageElementType::try_from_primitive(metadata.image_element_type)?;
+        let transmission_element_type =
+            TransmissionElementType::try_from_primitive(metadata.transmission_element_type)?;
+        let shape = ndarray::Ix3(
+            usize::try_from(metadata.dimension_1)?,
+            usize::try_from(metadata.dimension_2)?,
+            match ImageArrayRank::try_from_primitive(metadata.rank)? {
+                ImageArrayRank::Rank2 => {
+                    eyre::ensure!(
+                        metadata.dimension_3 == 0_i32,
+                        "dimension 3 must be 0 for rank 2, got {}",
+                        metadata.dimension_3,
+                    );
+                    1
+                }
+                ImageArrayRank::Rank3 => usize::try_from(metadata.dimension_3)?,
+            },
+        );
+        // Check the claimed dimensions before allocating anything based on them.
+        eyre::ensure!(
+            shape
+                .size_checked()
+                .is_some_and(|num_pixels| num_pixels <= max_image_pixels),
+            "image dimensions {shape:?} exceed the limit of {max_image_pixels} pixels",
+        );
+        Ok(Self {
+            transaction,
+            data_start,
+            content: Ok((shape, transmission_element_type)),
+        })
+    }
+
+    /// Build the ASCOM error response from the message following the metadata.
+    fn into_error_response(
+        self,
+        message: &[u8],
+    ) -> eyre::Result<ResponseWithTransaction<ASCOMResult<ImageArray>>> {
+        let Err(code) = self.content else {
+            eyre::bail!("image header doesn't describe an error");
+        };
+        Ok(ResponseWithTransaction {
+            transaction: self.transaction,
+            response: Err(ASCOMError::new(
+                code,
+                std::str::from_utf8(message)?.to_owned(),
+            )),
+        })
+    }
+}
+
+/// Decoder that converts ImageBytes pixels as they arrive, so that the raw body is never
+/// held in memory in full alongside the decoded image.
+struct ImageBytesDecoder {
+    max_image_pixels: usize,
+    /// Received bytes that haven't been decoded yet.
+    ///
+    /// This is everything up to the pixel data, the whole body for error responses,
+    /// and at most one partial element afterwards.
+    pending: Vec<u8>,
+    header: Option<ImageBytesHeader>,
+    /// Pixels decoded so far, preallocated once the pixel data starts.
+    data: Option<Vec<i32>>,
+}
+
+impl StreamDecoder<ASCOMResult<ImageArray>> for ImageBytesDecoder {
+    fn push(&mut self, chunk: &[u8]) -> eyre::Result<()> {
+        self.pending.extend_from_slice(chunk);
+        let header = match &self.header {
+            Some(header) => header,
+            None if self.pending.len() < size_of::<ImageBytesMetadata>() => return Ok(()),
+            None => self.header.insert(ImageBytesHeader::parse(
+                &self.pending,
+                self.max_image_pixels,
+            )?),
+        };
+        let Ok((shape, element_type)) = header.content else {
+            return Ok(());
+        };
+        let data = match &mut self.data {
+            Some(data) => data,
+            None if self.pending.len() < header.data_start => return Ok(()),
+            None => {
+                drop(self.pending.drain(..header.data_start));
+                self.data.insert(Vec::with_capacity(shape.size()))
+            }
+        };
+        let element_size = element_type.size();
+        let whole_len = self.pending.len() / element_size * element_size;
+        eyre::ensure!(
+            data.len() + whole_len / element_size <= shape.size(),
+            "image data is larger than dimensions {shape:?}",
+        );
+        let whole = &self.pending[..whole_len];
+        match element_type {
+            TransmissionElementType::I16 => extend_unaligned::<i16>(data, whole),
+            TransmissionElementType::I32 => extend_unaligned::<i32>(data, whole),
+            TransmissionElementType::U8 => extend_unaligned::<u8>(data, whole),
+            TransmissionElementType::U16 => extend_unaligned::<u16>(data, whole),
+        }
+        drop(self.pending.drain(..whole_len));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> eyre::Result<ResponseWithTransaction<ASCOMResult<ImageArray>>> {
+        let header = self
+            .header
+            .ok_or_else(|| eyre::eyre!("not enough bytes to read image metadata"))?;
+        let Ok((shape, transmission_element_type)) = header.content else {
+            let message = self
+                .pending
+                .get(header.data_start..)
+                .ok_or_else(|| eyre::eyre!("image data start offset is out of bounds"))?;
+            return header.into_error_response(message);
+        };
+        eyre::ensure!(
+            self.pending.is_empty(),
+            "image data ends with a partial element",
+        );
+        Ok(ResponseWithTransaction {
+            transaction: header.transaction,
+            response: Ok(ImageArray {
+                data: Array3::from_shape_vec(shape, self.data.unwrap_or_default())?.into_shared(),
+                transmission_element_type,
+            }),
+        })
+    }
+}
+
+/// Response type that only records whether the server replied in the ImageBytes format.
+pub(crate) struct ImageBytesProbe(pub(crate) bool);
+
+impl Response for ImageBytesProbe {
+    fn prepare_reqwest(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request.header(reqwest::header::ACCEPT, IMAGE_BYTES_TYPE)
+    }
+
+    fn from_reqwest(
+        mime_type: Mime,
+        bytes: &[u8],
+        DecodeOptions {
+            parsing,
+            max_image_pixels,
+        }: DecodeOptions,
+    ) -> eyre::Result<ResponseWithTransaction<Self>> {
+        if mime_type.essence_str() != IMAGE_BYTES_TYPE {
+            return Ok(ResponseWithTransaction {
+                transaction: ResponseTransaction::from_reqwest(mime_type, bytes, parsing)?,
+                response: Self(false),
+            });
+        }
+        let header = ImageBytesHeader::parse(bytes, max_image_pixels)?;
+        Ok(ResponseWithTransaction {
+            transaction: header.transaction,
+            response: Self(true),
+        })
+    }
+}
+
+impl Response for ASCOMResult<ImageArray> {
+    fn prepare_reqwest(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request.header(reqwest::header::ACCEPT, IMAGE_BYTES_TYPE)
+    }
+
+    fn from_reqwest(
+        mime_type: Mime,
+        bytes: &[u8],
+        DecodeOptions {
+            parsing,
+            max_image_pixels,
+        }: DecodeOptions,
+    ) -> eyre::Result<ResponseWithTransaction<Self>> {
+        if mime_type.essence_str() != IMAGE_BYTES_TYPE {
+            let transaction = ResponseTransaction::from_reqwest(mime_type, bytes, parsing)?;
+            let ascom_error = parsing.from_slice::<ASCOMError>(bytes)?;
+
+            return Ok(ResponseWithTransaction {
+                transaction,
+                response: match ascom_error.code {
+                    ASCOMErrorCode::OK => Ok(parsing.from_slice::<JsonImageArray>(bytes)?.0),
+                    _ => Err(ascom_error),
                 },
-            );
-            Ok(ndarray::Array::from_shape_vec(shape, data)?.into())
-        } else {
-            Err(ASCOMError::new(
-                ASCOMErrorCode::try_from(u16::try_from(metadata.error_number)?)?,
-                std::str::from_utf8(raw_data)?.to_owned(),
-            ))
+            });
+        }
+        let header = ImageBytesHeader::parse(bytes, max_image_pixels)?;
+        let raw_data = bytes
+            .get(header.data_start..)
+            .ok_or_else(|| eyre::eyre!("image data start offset is out of bounds"))?;
+        let Ok((shape, transmission_element_type)) = header.content else {
+            return header.into_error_response(raw_data);
         };
+        let data = match transmission_element_type {
+            TransmissionElementType::I16 => cast_raw_data::<i16>(raw_data),
+            TransmissionElementType::I32 => cast_raw_data::<i32>(raw_data),
+            TransmissionElementType::U8 => cast_raw_data::<u8>(raw_data),
+            TransmissionElementType::U16 => cast_raw_data::<u16>(raw_data),
+        }?;
         Ok(ResponseWithTransaction {
-            transaction,
-            response: ascom_result,
+            transaction: header.transaction,
+            response: Ok(ImageArray {
+                data: Array3::from_shape_vec(shape, data)?.into_shared(),
+                transmission_element_type,
+            }),
         })
     }
+
+    fn stream_decoder(
+        mime_type: &Mime,
+        DecodeOptions {
+            max_image_pixels, ..
+        }: DecodeOptions,
+    ) -> Option<Box<dyn StreamDecoder<Self>>> {
+        if mime_type.essence_str() != IMAGE_BYTES_TYPE {
+            return None;
+        }
+        Some(Box::new(ImageBytesDecoder {
+            max_image_pixels,
+            pending: Vec::new(),
+            header: None,
+            data: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn image_bytes_decode_in_chunks() {
+        let pixels = [1_u16, 2, 3, 40_000, 5, 6];
+        let metadata = ImageBytesMetadata {
+            metadata_version: 1,
+            error_number: 0,
+            client_transaction_id: NonZeroU32::new(1),
+            server_transaction_id: NonZeroU32::new(2),
+            data_start: i32::try_from(size_of::<ImageBytesMetadata>())
+                .expect("metadata size should fit into i32"),
+            image_element_type: ImageElementType::I32.into(),
+            transmission_element_type: TransmissionElementType::U16.into(),
+            rank: ImageArrayRank::Rank2.into(),
+            dimension_1: 2,
+            dimension_2: 3,
+            dimension_3: 0,
+        };
+        let mut bytes = bytemuck::bytes_of(&metadata).to_vec();
+        bytes.extend_from_slice(bytemuck::cast_slice(&pixels));
+
+        let mime_type = IMAGE_BYTES_TYPE
+            .parse::<Mime>()
+            .expect("ImageBytes mime type should parse");
+        let options = DecodeOptions::default();
+        let buffered = ASCOMResult::<ImageArray>::from_reqwest(mime_type.clone(), &bytes, options)
+            .expect("buffered decoding should succeed")
+            .response
+            .expect("image should not be an error");
+
+        // Odd-sized chunks split both the metadata and individual pixels.
+        let mut decoder = ASCOMResult::<ImageArray>::stream_decoder(&mime_type, options)
+            .expect("ImageBytes should be decoded as a stream");
+        for chunk in bytes.chunks(3) {
+            decoder.push(chunk).expect("chunk should be accepted");
+        }
+        let streamed = decoder
+            .finish()
+            .expect("streamed decoding should succeed")
+            .response
+            .expect("image should not be an error");
+
+        assert_eq!(streamed, buffered);
+        assert_eq!(
+            streamed.transmission_element_type(),
+            TransmissionElementType::U16
+        );
+        assert_eq!(streamed[[1, 1, 0]], 40_000);
+    }
+
+    #[test]
+    fn json_pixels_are_range_checked() {
+        let image = serde_json::from_str::<JsonImageArray>(
+            r#"{"Type":2,"Rank":2,"Value":[[1,-2],[2147483647,-2147483648]]}"#,
+        )
+        .expect("in-range pixels should parse")
+        .0;
+        assert_eq!(image[[1, 0, 0]], i32::MAX);
+        assert_eq!(image[[1, 1, 0]], i32::MIN);
+
+        let Err(err) = serde_json::from_str::<JsonImageArray>(
+            r#"{"Type":2,"Rank":2,"Value":[[1,2],[3,2147483648]]}"#,
+        ) else {
+            panic!("out-of-range pixel should be rejected");
+        };
+        assert!(
+            err.to_string().contains("pixel value 2147483648"),
+            "unexpected error: {err}"
+        );
+    }
 }
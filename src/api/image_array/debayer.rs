@@ -0,0 +1,217 @@
+use super::{ImageArray, COLOUR_AXIS};
+use crate::api::SensorType;
+#[cfg(feature = "client")]
+use crate::{ASCOMError, ASCOMResult};
+use ndarray::{s, Array3, ArrayView2};
+
+/// Output channel of each cell of a colour filter array, as rows repeated across the sensor.
+///
+/// Rows are indexed by `y` and cells within a row by `x`.
+type Pattern = &'static [&'static [usize]];
+
+/// Red, green / green, blue.
+const RGGB: Pattern = &[&[0, 1], &[1, 2]];
+
+// Channels of complementary colour patterns.
+const CYAN: usize = 0;
+const MAGENTA: usize = 1;
+const YELLOW: usize = 2;
+const GREEN: usize = 3;
+
+/// Cyan, yellow / green, magenta.
+const CMYG: Pattern = &[&[CYAN, YELLOW], &[GREEN, MAGENTA]];
+
+/// Like [`CMYG`], but with green and magenta swapped on every other row pair.
+const CMYG2: Pattern = &[
+    &[CYAN, YELLOW],
+    &[GREEN, MAGENTA],
+    &[CYAN, YELLOW],
+    &[MAGENTA, GREEN],
+];
+
+/// Kodak TRUESENSE pattern, with luminance as the last channel.
+const LRGB: Pattern = &[&[3, 0, 3, 1], &[0, 3, 1, 3], &[3, 1, 3, 2], &[1, 3, 2, 3]];
+
+/// Bilinear demosaicing of a single-plane image taken through a colour filter array.
+///
+/// `offset_x` and `offset_y` shift the pattern relative to the image origin, as reported by
+/// `BayerOffsetX` / `BayerOffsetY`. Each missing channel value is the average of the pixels
+/// of that channel in the surrounding block spanning one pattern period, which is simply
+/// cut off at image edges.
+fn bilinear(
+    raw: ArrayView2<'_, i32>,
+    pattern: Pattern,
+    num_channels: usize,
+    offset_x: usize,
+    offset_y: usize,
+) -> Array3<i32> {
+    let (width, height) = raw.dim();
+    let (period_x, period_y) = (pattern[0].len(), pattern.len());
+    let channel_at =
+        |x: usize, y: usize| pattern[(y + offset_y) % period_y][(x + offset_x) % period_x];
+
+    Array3::from_shape_fn((width, height, num_channels), |(x, y, channel)| {
+        if channel_at(x, y) == channel {
+            return raw[[x, y]];
+        }
+        let (mut sum, mut count) = (0_i64, 0_i64);
+        let (radius_x, radius_y) = (period_x / 2, period_y / 2);
+        for neighbour_x in x.saturating_sub(radius_x)..=(x + radius_x).min(width - 1) {
+            for neighbour_y in y.saturating_sub(radius_y)..=(y + radius_y).min(height - 1) {
+                if channel_at(neighbour_x, neighbour_y) == channel {
+                    sum += i64::from(raw[[neighbour_x, neighbour_y]]);
+                    count += 1;
+                }
+            }
+        }
+        if count == 0 {
+            return 0;
+        }
+        i32::try_from((sum + count / 2).div_euclid(count))
+            .expect("internal error: average of i32 values should fit into i32")
+    })
+}
+
+/// Convert interpolated cyan, magenta, yellow and green planes to RGB.
+///
+/// Complementary colours are sums of two primaries (C = G + B, M = R + B, Y = R + G),
+/// which gives red and blue directly, while green is measured by its own pixels.
+fn cmyg_to_rgb(cmyg: &Array3<i32>) -> Array3<i32> {
+    let (width, height, _) = cmyg.dim();
+    Array3::from_shape_fn((width, height, 3), |(x, y, channel)| {
+        let at = |channel: usize| i64::from(cmyg[[x, y, channel]]);
+        let value = match channel {
+            0 => (at(MAGENTA) + at(YELLOW) - at(CYAN)) / 2,
+            1 => at(GREEN),
+            _ => (at(CYAN) + at(MAGENTA) - at(YELLOW)) / 2,
+        };
+        i32::try_from(value.clamp(0, i64::from(i32::MAX)))
+            .expect("internal error: clamped value should fit into i32")
+    })
+}
+
+#[cfg(feature = "client")]
+fn saturate_u16(value: i32) -> u16 {
+    u16::try_from(value).unwrap_or(if value < 0 { 0 } else { u16::MAX })
+}
+
+impl ImageArray {
+    /// Demosaic a single-plane image from a colour sensor into a 3-plane RGB image.
+    ///
+    /// `offset_x` and `offset_y` are the camera's [`BayerOffsetX`](crate::api::Camera::bayer_offset_x)
+    /// and [`BayerOffsetY`](crate::api::Camera::bayer_offset_y). Missing colour values are
+    /// computed with bilinear interpolation from the neighbouring pixels, using fewer neighbours
+    /// at the image edges. For [`SensorType::LRGB`] the luminance pixels are only used as gaps
+    /// between colour ones, and [`SensorType::CMYG`] / [`SensorType::CMYG2`] are converted from
+    /// complementary colours.
+    ///
+    /// [`SensorType::Monochrome`] and [`SensorType::Color`] images, as well as images that
+    /// don't have exactly one plane, are returned unchanged.
+    pub fn debayer(&self, sensor_type: SensorType, offset_x: i32, offset_y: i32) -> Self {
+        if self.len_of(COLOUR_AXIS) != 1 {
+            return self.clone();
+        }
+        // All patterns have periods of 2 or 4, so reducing offsets modulo 4 preserves them.
+        let offset = |offset: i32| {
+            usize::try_from(offset.rem_euclid(4)).expect("euclidean remainder should be positive")
+        };
+        let (offset_x, offset_y) = (offset(offset_x), offset(offset_y));
+        let raw = self.slice(s![.., .., 0]);
+        let rgb = match sensor_type {
+            SensorType::Monochrome | SensorType::Color => return self.clone(),
+            SensorType::RGGB => bilinear(raw, RGGB, 3, offset_x, offset_y),
+            SensorType::CMYG => cmyg_to_rgb(&bilinear(raw, CMYG, 4, offset_x, offset_y)),
+            SensorType::CMYG2 => cmyg_to_rgb(&bilinear(raw, CMYG2, 4, offset_x, offset_y)),
+            SensorType::LRGB => {
+                bilinear(raw, LRGB, 4, offset_x, offset_y).slice_move(s![.., .., ..3])
+            }
+        };
+        Self {
+            data: rgb.into_shared(),
+            // Interpolated values stay within the range of the original ones.
+            transmission_element_type: self.transmission_element_type,
+        }
+    }
+
+    #[cfg(feature = "client")]
+    fn ensure_planes(&self, sensor_type: SensorType, expected: usize) -> ASCOMResult {
+        let planes = self.len_of(COLOUR_AXIS);
+        if planes != expected {
+            return Err(ASCOMError::invalid_value(format_args!(
+                "expected {expected} plane(s) for {sensor_type:?} sensor, got {planes}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Convert the image to 16-bit RGB according to the camera's sensor type.
+    ///
+    /// `bayer_offset` is only used for Bayer sensors.
+    #[cfg(feature = "client")]
+    pub(crate) fn to_rgb_u16(
+        &self,
+        sensor_type: SensorType,
+        (offset_x, offset_y): (i32, i32),
+    ) -> ASCOMResult<Array3<u16>> {
+        let rgb = match sensor_type {
+            SensorType::Monochrome => {
+                self.ensure_planes(sensor_type, 1)?;
+                let (width, height, _) = self.dim();
+                self.broadcast((width, height, 3))
+                    .ok_or_else(|| ASCOMError::unspecified("failed to broadcast monochrome image"))?
+                    .mapv(saturate_u16)
+            }
+            SensorType::Color => {
+                self.ensure_planes(sensor_type, 3)?;
+                self.mapv(saturate_u16)
+            }
+            SensorType::RGGB | SensorType::CMYG | SensorType::CMYG2 | SensorType::LRGB => {
+                self.ensure_planes(sensor_type, 1)?;
+                self.debayer(sensor_type, offset_x, offset_y)
+                    .mapv(saturate_u16)
+            }
+        };
+        Ok(rgb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn rggb_uniform_channels_are_interpolated() {
+        // Red = 10, green = 20, blue = 30 in an RGGB layout with no offset.
+        let raw = Array2::from_shape_fn((4, 4), |(x, y)| match (x % 2, y % 2) {
+            (0, 0) => 10_i32,
+            (1, 1) => 30,
+            _ => 20,
+        });
+        let rgb = ImageArray::from(raw).debayer(SensorType::RGGB, 0, 0);
+        assert_eq!(rgb.dim(), (4, 4, 3));
+        for (channel, expected) in [10, 20, 30].into_iter().enumerate() {
+            assert!(
+                rgb.plane(channel)
+                    .expect("RGB image should have 3 planes")
+                    .iter()
+                    .all(|&value| value == expected),
+                "channel {channel} isn't uniformly {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn non_bayer_images_are_unchanged() {
+        let image = ImageArray::from(Array2::from_elem((3, 5), 7_u16));
+        assert_eq!(image.debayer(SensorType::Monochrome, 0, 0), image);
+    }
+
+    #[test]
+    fn small_odd_sized_images_are_supported() {
+        let image = ImageArray::from(Array2::from_elem((3, 5), 7_u16));
+        for sensor_type in [SensorType::RGGB, SensorType::CMYG2, SensorType::LRGB] {
+            assert_eq!(image.debayer(sensor_type, -1, 3).dim(), (3, 5, 3));
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use super::{ImageArray, Stretch, COLOUR_AXIS};
+use image::{DynamicImage, ImageBuffer};
+
+impl ImageArray {
+    /// Convert the image to a 16-bit [`DynamicImage`] for previews, e.g. in GUI applications.
+    ///
+    /// 2D images are converted to [`DynamicImage::ImageLuma16`] and 3D images with 3 planes to
+    /// [`DynamicImage::ImageRgb16`]. Values are scaled to the full `u16` range according to
+    /// `stretch`, saturating the ones that fall outside of it.
+    ///
+    /// Returns `None` for other numbers of planes and for images too large for the `image` crate.
+    pub fn to_dynamic_image(&self, stretch: Stretch) -> Option<DynamicImage> {
+        let (width, height, _) = self.dim();
+        let (width, height) = (u32::try_from(width).ok()?, u32::try_from(height).ok()?);
+        let data = self.stretch_row_major(stretch, u16::MAX);
+        match self.len_of(COLOUR_AXIS) {
+            1 => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageLuma16),
+            3 => ImageBuffer::from_raw(width, height, data).map(DynamicImage::ImageRgb16),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array2, Array3};
+
+    #[test]
+    fn saturates_outside_of_range() {
+        let image = ImageArray::from(
+            Array2::from_shape_vec((2, 1), vec![-5_i32, 70_000]).expect("shape should match"),
+        );
+        let image = image
+            .to_dynamic_image(Stretch::Range {
+                black: 0,
+                white: 65_535,
+            })
+            .expect("2D image should be convertible");
+        assert_eq!(
+            image.as_luma16().map(|image| image.as_raw().as_slice()),
+            Some([0, u16::MAX].as_slice())
+        );
+    }
+
+    #[test]
+    fn rgb_is_row_major() {
+        // Number the samples in the order they should appear in the output.
+        let image = ImageArray::from(Array3::from_shape_fn((2, 2, 3), |(x, y, plane)| {
+            i32::try_from((y * 2 + x) * 3 + plane).expect("small value should fit")
+        }));
+        let image = image
+            .to_dynamic_image(Stretch::Range {
+                black: 0,
+                white: 65_535,
+            })
+            .expect("RGB image should be convertible");
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(
+            image.as_rgb16().map(|image| image.as_raw().as_slice()),
+            Some([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11].as_slice())
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use super::{ImageArray, ImageArrayRank, TransmissionElementType};
+use crate::api::time_repr::{Fits, FormatWrapper};
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+use time::OffsetDateTime;
+
+/// Size of FITS header and data blocks.
+const BLOCK_SIZE: usize = 2880;
+
+/// Size of a single header card.
+const CARD_SIZE: usize = 80;
+
+/// Optional header cards to include in a FITS file.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FitsHeader {
+    /// Start of the exposure, written as `DATE-OBS`.
+    ///
+    /// Usually obtained from [`Camera::last_exposure_start_time`](crate::api::Camera::last_exposure_start_time).
+    pub last_exposure_start_time: Option<SystemTime>,
+    /// CCD temperature in degrees Celsius, written as `CCD-TEMP`.
+    ///
+    /// Usually obtained from [`Camera::ccd_temperature`](crate::api::Camera::ccd_temperature).
+    pub ccd_temperature: Option<f64>,
+}
+
+/// Format a header card padded to its fixed size.
+///
+/// `value` must already be formatted according to its type.
+fn card(keyword: &str, value: &str, comment: &str) -> String {
+    let mut card = format!("{keyword:<8}= {value}");
+    if !comment.is_empty() {
+        card = format!("{card} / {comment}");
+    }
+    card.truncate(CARD_SIZE);
+    format!("{card:<CARD_SIZE$}")
+}
+
+fn int_card(keyword: &str, value: impl std::fmt::Display, comment: &str) -> String {
+    card(keyword, &format!("{value:>20}"), comment)
+}
+
+fn pad_to_block(bytes: &mut Vec<u8>, padding: u8) {
+    bytes.resize(bytes.len().next_multiple_of(BLOCK_SIZE), padding);
+}
+
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+impl ImageArray {
+    /// Encode the image as a FITS file.
+    ///
+    /// `BITPIX` matches the [transmission element type](Self::transmission_element_type), with
+    /// unsigned 16-bit images stored as signed values offset by `BZERO = 32768` as per the FITS
+    /// standard. `NAXIS1` is the image width (first axis of the Alpaca column-major array),
+    /// `NAXIS2` is the height, and `NAXIS3` is the number of colour planes for 3D images.
+    pub fn to_fits_bytes(&self, header: &FitsHeader) -> io::Result<Vec<u8>> {
+        let (width, height, planes) = self.dim();
+        let bitpix = match self.transmission_element_type {
+            TransmissionElementType::U8 => 8_i32,
+            TransmissionElementType::I16 | TransmissionElementType::U16 => 16,
+            TransmissionElementType::I32 => 32,
+        };
+
+        let mut cards = vec![
+            card(
+                "SIMPLE",
+                &format!("{:>20}", "T"),
+                "conforms to FITS standard",
+            ),
+            int_card("BITPIX", bitpix, "bits per data value"),
+        ];
+        match self.rank() {
+            ImageArrayRank::Rank2 => cards.extend([
+                int_card("NAXIS", 2_i32, "number of axes"),
+                int_card("NAXIS1", width, "image width"),
+                int_card("NAXIS2", height, "image height"),
+            ]),
+            ImageArrayRank::Rank3 => cards.extend([
+                int_card("NAXIS", 3_i32, "number of axes"),
+                int_card("NAXIS1", width, "image width"),
+                int_card("NAXIS2", height, "image height"),
+                int_card("NAXIS3", planes, "number of colour planes"),
+            ]),
+        }
+        if self.transmission_element_type == TransmissionElementType::U16 {
+            cards.extend([
+                int_card("BZERO", 32768_i32, "offset for unsigned 16-bit values"),
+                int_card("BSCALE", 1_i32, "default scaling"),
+            ]);
+        }
+        if let Some(start_time) = header.last_exposure_start_time {
+            let start_time = OffsetDateTime::from(start_time)
+                .format(Fits::FORMAT)
+                .map_err(invalid_data)?;
+            cards.push(card(
+                "DATE-OBS",
+                &format!("'{start_time}'"),
+                "UTC start of exposure",
+            ));
+        }
+        if let Some(temperature) = header.ccd_temperature.filter(|t| t.is_finite()) {
+            // FITS requires an uppercase exponent letter.
+            let temperature = format!("{temperature:?}").to_uppercase();
+            cards.push(card(
+                "CCD-TEMP",
+                &format!("{temperature:>20}"),
+                "CCD temperature in C",
+            ));
+        }
+        cards.push(format!("{:<CARD_SIZE$}", "END"));
+
+        let mut bytes = cards.concat().into_bytes();
+        pad_to_block(&mut bytes, b' ');
+
+        // FITS stores the first axis as the fastest varying one.
+        let pixels = self.data.view().reversed_axes();
+        for &value in &pixels {
+            match self.transmission_element_type {
+                TransmissionElementType::U8 => {
+                    bytes.push(u8::try_from(value).map_err(invalid_data)?);
+                }
+                TransmissionElementType::I16 => {
+                    bytes.extend(i16::try_from(value).map_err(invalid_data)?.to_be_bytes());
+                }
+                TransmissionElementType::U16 => {
+                    // Flipping the sign bit is the same as subtracting BZERO.
+                    let value = u16::try_from(value).map_err(invalid_data)? ^ 0x8000;
+                    bytes.extend(value.to_be_bytes());
+                }
+                TransmissionElementType::I32 => bytes.extend(value.to_be_bytes()),
+            }
+        }
+        pad_to_block(&mut bytes, 0);
+
+        Ok(bytes)
+    }
+
+    /// Save the image as a FITS file.
+    ///
+    /// See [`to_fits_bytes`](Self::to_fits_bytes) for details on the format.
+    pub fn write_fits(&self, path: impl AsRef<Path>, header: &FitsHeader) -> io::Result<()> {
+        std::fs::write(path, self.to_fits_bytes(header)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{Array2, Array3};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    /// Minimal parser for the subset of FITS produced above.
+    fn parse(bytes: &[u8]) -> (HashMap<String, String>, &[u8]) {
+        let mut cards = HashMap::new();
+        for (index, card) in bytes.chunks_exact(CARD_SIZE).enumerate() {
+            let card = std::str::from_utf8(card).expect("header should be ASCII");
+            if card.trim_end() == "END" {
+                let data_start = ((index + 1) * CARD_SIZE).next_multiple_of(BLOCK_SIZE);
+                return (cards, &bytes[data_start..]);
+            }
+            let (keyword, value) = card.split_once("= ").expect("card should have a value");
+            let value = value.split(" / ").next().unwrap_or_default().trim();
+            let _ = cards.insert(keyword.trim_end().to_owned(), value.to_owned());
+        }
+        panic!("header should end with END");
+    }
+
+    #[test]
+    fn u16_round_trip() {
+        let image = ImageArray::from(Array2::from_shape_fn((3_usize, 2_usize), |(x, y)| {
+            [0_u16, 1, 1000, 32768, 40000, u16::MAX][x + y * 3]
+        }));
+        let header = FitsHeader {
+            last_exposure_start_time: Some(SystemTime::UNIX_EPOCH + Duration::from_millis(1_500)),
+            ccd_temperature: Some(-10.5),
+        };
+        let bytes = image
+            .to_fits_bytes(&header)
+            .expect("image should be encodable");
+        assert_eq!(bytes.len() % BLOCK_SIZE, 0);
+
+        let (cards, data) = parse(&bytes);
+        assert_eq!(cards["SIMPLE"], "T");
+        assert_eq!(cards["BITPIX"], "16");
+        assert_eq!(cards["NAXIS"], "2");
+        assert_eq!(cards["NAXIS1"], "3");
+        assert_eq!(cards["NAXIS2"], "2");
+        assert_eq!(cards["BZERO"], "32768");
+        assert_eq!(cards["DATE-OBS"], "'1970-01-01T00:00:01.500'");
+        assert_eq!(cards["CCD-TEMP"], "-10.5");
+
+        let values: Vec<i32> = data
+            .chunks_exact(2)
+            .take(6)
+            .map(|chunk| i32::from(i16::from_be_bytes([chunk[0], chunk[1]])) + 32768)
+            .collect();
+        assert_eq!(values, [0, 1, 1000, 32768, 40000, 65535]);
+    }
+
+    #[test]
+    fn rank3_layout() {
+        let image = ImageArray::from(Array3::from_shape_fn(
+            (2_usize, 1_usize, 3_usize),
+            |(x, _, plane)| i32::try_from(plane * 10 + x).expect("small value should fit"),
+        ));
+        let bytes = image
+            .to_fits_bytes(&FitsHeader::default())
+            .expect("image should be encodable");
+
+        let (cards, data) = parse(&bytes);
+        assert_eq!(cards["BITPIX"], "32");
+        assert_eq!(cards["NAXIS"], "3");
+        assert_eq!(cards["NAXIS3"], "3");
+        assert!(!cards.contains_key("DATE-OBS"));
+
+        let values: Vec<i32> = data
+            .chunks_exact(4)
+            .take(6)
+            .map(|chunk| i32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        assert_eq!(values, [0, 1, 10, 11, 20, 21]);
+    }
+}
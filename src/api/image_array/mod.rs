@@ -1,15 +1,31 @@
 #[cfg(feature = "client")]
 mod client;
+#[cfg(feature = "client")]
+pub(crate) use client::ImageBytesProbe;
 #[cfg(feature = "server")]
 mod server;
 
 #[cfg(feature = "server")]
 pub(crate) use server::ImageBytesResponse;
 
+mod debayer;
+
+#[cfg(feature = "image-interop")]
+mod dynamic_image;
+
+#[cfg(feature = "fits")]
+mod fits;
+#[cfg(feature = "fits")]
+pub use fits::FitsHeader;
+
+mod stretch;
+pub use stretch::Stretch;
+
 use bytemuck::{AnyBitPattern, Pod, Zeroable};
-use ndarray::{Array2, Array3, ArrayView2, ArrayView3, Axis};
+use ndarray::{s, Array2, Array3, ArrayView2, ArrayView3, Axis};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::num::NonZeroU32;
 use std::ops::Deref;
 
@@ -21,6 +37,9 @@ type ArcArray3<T> = ndarray::ArcArray<T, ndarray::Ix3>;
     Debug,
     PartialEq,
     Eq,
+    PartialOrd,
+    Ord,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -36,12 +55,19 @@ pub enum ImageArrayRank {
     Rank3 = 3_i32,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, IntoPrimitive, TryFromPrimitive)]
+/// Element type the image is transmitted as in the `ImageBytes` format.
+///
+/// This is the native bit depth of the image as provided by the driver.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, IntoPrimitive, TryFromPrimitive)]
 #[repr(i32)]
-pub(crate) enum TransmissionElementType {
+pub enum TransmissionElementType {
+    /// Signed 16-bit integer.
     I16 = 1,
+    /// Signed 32-bit integer.
     I32 = 2,
+    /// Unsigned 8-bit integer.
     U8 = 6,
+    /// Unsigned 16-bit integer.
     U16 = 8,
 }
 
@@ -149,6 +175,106 @@ impl ImageArray {
             _ => ImageArrayRank::Rank3,
         }
     }
+
+    /// Extract a rectangular region of the image.
+    ///
+    /// `x` and `width` are along the first (column) axis, `y` and `height` along the second (row) axis,
+    /// matching the Alpaca column-major convention. All colour planes are preserved.
+    ///
+    /// The returned image shares the underlying data with this one, so no pixels are copied.
+    ///
+    /// Returns `None` if the region is empty or doesn't fit within the image.
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Self> {
+        let fits = |start: usize, len: usize, axis: usize| {
+            len > 0
+                && start
+                    .checked_add(len)
+                    .is_some_and(|end| end <= self.data.len_of(Axis(axis)))
+        };
+        if !fits(x, width, 0) || !fits(y, height, 1) {
+            return None;
+        }
+        Some(Self {
+            data: self
+                .data
+                .clone()
+                .slice_move(s![x..x + width, y..y + height, ..]),
+            transmission_element_type: self.transmission_element_type,
+        })
+    }
+
+    /// Get a view of a single colour plane, e.g. the red channel of an RGB image.
+    ///
+    /// Monochrome images have a single plane at index 0. Returns `None` if `index` is out of range.
+    pub fn plane(&self, index: usize) -> Option<ArrayView2<'_, i32>> {
+        (index < self.data.len_of(COLOUR_AXIS)).then(|| self.data.index_axis(COLOUR_AXIS, index))
+    }
+
+    /// Copy each colour plane into a separate 2D array.
+    ///
+    /// Use [`plane`](Self::plane) to access planes without copying.
+    pub fn planes(&self) -> Vec<Array2<i32>> {
+        self.data
+            .axis_iter(COLOUR_AXIS)
+            .map(ArrayView2::to_owned)
+            .collect()
+    }
+
+    /// Get the element type the image was created from or transmitted as.
+    pub const fn transmission_element_type(&self) -> TransmissionElementType {
+        self.transmission_element_type
+    }
+
+    fn as_narrow_array<T: TryFrom<i32>>(&self) -> Option<Array3<T>> {
+        let values = self
+            .data
+            .iter()
+            .map(|&value| T::try_from(value).ok())
+            .collect::<Option<Vec<T>>>()?;
+        Some(
+            Array3::from_shape_vec(self.data.raw_dim(), values)
+                .expect("element count should match the original shape"),
+        )
+    }
+
+    /// Copy the image into an array of `i16` values.
+    ///
+    /// Returns `None` if any value doesn't fit into `i16`.
+    pub fn as_i16_array(&self) -> Option<Array3<i16>> {
+        self.as_narrow_array()
+    }
+
+    /// Copy the image into an array of `u16` values.
+    ///
+    /// Returns `None` if any value doesn't fit into `u16`.
+    pub fn as_u16_array(&self) -> Option<Array3<u16>> {
+        self.as_narrow_array()
+    }
+
+    /// Copy the image into an array of `u8` values.
+    ///
+    /// Returns `None` if any value doesn't fit into `u8`.
+    pub fn as_u8_array(&self) -> Option<Array3<u8>> {
+        self.as_narrow_array()
+    }
+
+    /// Compute a hash of the image contents, e.g. for skipping unchanged frames in a live preview.
+    ///
+    /// Images that compare equal always have the same hash, regardless of their memory layout.
+    /// The hash covers shape, transmission element type and pixel values, and is only meant for
+    /// comparisons within the same process, as the algorithm may change between Rust versions.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.data.shape().hash(&mut hasher);
+        self.transmission_element_type.hash(&mut hasher);
+        hasher.write(bytemuck::cast_slice(
+            self.data
+                .as_standard_layout()
+                .as_slice()
+                .expect("standard layout array should be contiguous"),
+        ));
+        hasher.finish()
+    }
 }
 
 #[cfg(not(target_endian = "little"))]
@@ -0,0 +1,147 @@
+use super::ImageArray;
+
+/// Stretch applied when converting an [`ImageArray`] to display data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stretch {
+    /// Map the minimum value of the image to black and the maximum to white.
+    Linear,
+    /// Map the given low and high percentiles (in `0.0..=100.0`) to black and white,
+    /// clipping values outside of that range.
+    ///
+    /// Percentiles outside of `0.0..=100.0` are clamped, and swapped if `low > high`.
+    ///
+    /// For example, `Percentile { low: 0.5, high: 99.5 }` ignores hot pixels and
+    /// the darkest noise when choosing the display range.
+    Percentile {
+        /// Percentile mapped to black.
+        low: f64,
+        /// Percentile mapped to white.
+        high: f64,
+    },
+    /// Map the given values to black and white, clipping values outside of that range.
+    ///
+    /// For example, `Range { black: 0, white: max_adu }` shows the full range of the sensor.
+    Range {
+        /// Value mapped to black.
+        black: i32,
+        /// Value mapped to white.
+        white: i32,
+    },
+}
+
+// Float-to-int `as` conversions saturate and map NaN to 0; the upper end is clamped separately.
+#[allow(
+    clippy::as_conversions,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn percentile_index(len: usize, percentile: f64) -> usize {
+    let percentile = percentile.clamp(0.0, 100.0);
+    let index = (((len - 1) as f64) * (percentile / 100.0)).round() as usize;
+    index.min(len - 1)
+}
+
+impl ImageArray {
+    /// Find the values mapped to black and white by the given stretch.
+    ///
+    /// Returns `None` for empty images.
+    fn stretch_range(&self, stretch: Stretch) -> Option<(i32, i32)> {
+        match stretch {
+            Stretch::Linear => self.iter().fold(None, |range, &value| match range {
+                None => Some((value, value)),
+                Some((min, max)) => Some((value.min(min), value.max(max))),
+            }),
+            Stretch::Percentile { low, high } => {
+                let mut values = self.iter().copied().collect::<Vec<_>>();
+                if values.is_empty() {
+                    return None;
+                }
+                let len = values.len();
+                let (low, high) = if low > high { (high, low) } else { (low, high) };
+                let black = *values.select_nth_unstable(percentile_index(len, low)).1;
+                let white = *values.select_nth_unstable(percentile_index(len, high)).1;
+                Some((black, white))
+            }
+            Stretch::Range { black, white } => (!self.is_empty()).then_some((black, white)),
+        }
+    }
+
+    /// Stretch the image to `0..=max` and return the values in row-major order.
+    pub(crate) fn stretch_row_major<T: Copy + Into<i64> + TryFrom<i64>>(
+        &self,
+        stretch: Stretch,
+        max: T,
+    ) -> Vec<T> {
+        let Some((black, white)) = self.stretch_range(stretch) else {
+            return Vec::new();
+        };
+        let black = i64::from(black);
+        let range = (i64::from(white) - black).max(1);
+        let max_value: i64 = max.into();
+
+        // ASCOM images are indexed as [x, y, plane], so swap the first two axes to get row-major order.
+        self.view()
+            .permuted_axes([1, 0, 2])
+            .iter()
+            .map(|&value| {
+                let scaled = (i64::from(value) - black).clamp(0, range) * max_value / range;
+                T::try_from(scaled).unwrap_or(max)
+            })
+            .collect()
+    }
+
+    /// Convert the image to an 8-bit buffer ready for display.
+    ///
+    /// The output is in row-major order. For 2D images it contains one grayscale byte per pixel,
+    /// and for 3D images all the planes of each pixel are interleaved (e.g. RGB).
+    pub fn to_display_u8(&self, stretch: Stretch) -> Vec<u8> {
+        self.stretch_row_major(stretch, u8::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    fn ramp() -> ImageArray {
+        ImageArray::from(Array2::from_shape_fn((10, 1), |(x, _)| {
+            i32::try_from(x).expect("index should fit in i32")
+        }))
+    }
+
+    #[test]
+    fn percentile_range() {
+        let image = ramp();
+        assert_eq!(
+            image.stretch_range(Stretch::Percentile {
+                low: 0.0,
+                high: 100.0
+            }),
+            Some((0, 9))
+        );
+        assert_eq!(
+            image.stretch_range(Stretch::Percentile {
+                low: 50.0,
+                high: 0.0
+            }),
+            Some((0, 5))
+        );
+    }
+
+    #[test]
+    fn out_of_range_percentiles_are_clamped() {
+        let image = ramp();
+        for (low, high) in [
+            (0.0, 101.0),
+            (-5.0, f64::INFINITY),
+            (f64::NEG_INFINITY, 1e300),
+        ] {
+            assert_eq!(
+                image.stretch_range(Stretch::Percentile { low, high }),
+                Some((0, 9))
+            );
+        }
+    }
+}
@@ -65,6 +65,18 @@ mod image_array;
 #[cfg(feature = "camera")]
 pub use image_array::*;
 
+#[cfg(feature = "camera")]
+mod camera_progress;
+
+#[cfg(feature = "camera")]
+pub use camera_progress::CameraProgress;
+
+#[cfg(feature = "client")]
+mod helpers;
+
+#[cfg(feature = "client")]
+pub use helpers::*;
+
 /// A DeviceState object representing an operational property of this device.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -83,11 +95,28 @@ pub struct DeviceStateItem {
 }
 
 /// Camera state.
+///
+/// # Example
+///
+/// ```
+/// use ascom_alpaca::api::CameraState;
+/// use std::collections::HashMap;
+///
+/// let mut time_in_state = HashMap::new();
+/// *time_in_state.entry(CameraState::Exposing).or_insert(0) += 5;
+/// assert_eq!(time_in_state[&CameraState::Exposing], 5);
+///
+/// // States are ordered by their progression through an exposure.
+/// assert!(CameraState::Exposing < CameraState::Download);
+/// ```
 #[cfg(feature = "camera")]
 #[derive(
     Debug,
     PartialEq,
     Eq,
+    PartialOrd,
+    Ord,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -123,6 +152,7 @@ pub enum CameraState {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -158,6 +188,7 @@ pub enum SensorType {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -183,6 +214,7 @@ pub enum GuideDirection {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -218,6 +250,7 @@ pub enum CalibratorStatus {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -253,6 +286,7 @@ pub enum CoverStatus {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -285,6 +319,7 @@ pub enum ShutterState {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -311,6 +346,7 @@ pub enum AlignmentMode {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -343,6 +379,7 @@ pub enum EquatorialCoordinateType {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -369,6 +406,7 @@ pub enum PierSide {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -414,6 +452,7 @@ pub struct AxisRate {
     Debug,
     PartialEq,
     Eq,
+    Hash,
     Clone,
     Copy,
     Serialize_repr,
@@ -468,7 +507,7 @@ pub trait Device: std::fmt::Debug + Send + Sync {
 
         #[http("Command")] command: String,
 
-        #[http("Raw")] raw: String,
+        #[http("Raw")] raw: bool,
     ) -> ASCOMResult {
         Err(ASCOMError::NOT_IMPLEMENTED)
     }
@@ -482,7 +521,7 @@ pub trait Device: std::fmt::Debug + Send + Sync {
 
         #[http("Command")] command: String,
 
-        #[http("Raw")] raw: String,
+        #[http("Raw")] raw: bool,
     ) -> ASCOMResult<bool> {
         Err(ASCOMError::NOT_IMPLEMENTED)
     }
@@ -496,7 +535,7 @@ pub trait Device: std::fmt::Debug + Send + Sync {
 
         #[http("Command")] command: String,
 
-        #[http("Raw")] raw: String,
+        #[http("Raw")] raw: bool,
     ) -> ASCOMResult<String> {
         Err(ASCOMError::NOT_IMPLEMENTED)
     }
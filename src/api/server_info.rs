@@ -12,6 +12,36 @@ pub(crate) struct ConfiguredDevice<DeviceType> {
     pub(crate) unique_id: String,
 }
 
+/// Persistable description of a registered device.
+///
+/// Obtained via [`Devices::descriptors`](crate::api::Devices::descriptors). A saved list of
+/// descriptors can be turned back into device clients for the same server via
+/// `Client::devices_from_descriptors` without re-running discovery or querying the device list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeviceDescriptor {
+    /// Device type, e.g. `Camera`.
+    pub device_type: String,
+    /// Device number within its type.
+    pub device_number: usize,
+    /// Device name.
+    pub device_name: String,
+    /// Unique device ID.
+    #[serde(rename = "UniqueID")]
+    pub unique_id: String,
+}
+
+impl<DeviceType: std::fmt::Display> From<ConfiguredDevice<DeviceType>> for DeviceDescriptor {
+    fn from(device: ConfiguredDevice<DeviceType>) -> Self {
+        Self {
+            device_type: device.ty.to_string(),
+            device_number: device.number,
+            device_name: device.name,
+            unique_id: device.unique_id,
+        }
+    }
+}
+
 /// General information about the server.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -1,4 +1,8 @@
-#![cfg(any(feature = "camera", feature = "telescope"))]
+#![cfg(any(
+    feature = "camera",
+    feature = "telescope",
+    all(feature = "client", feature = "__anydevice")
+))]
 
 use std::marker::PhantomData;
 use std::time::SystemTime;
@@ -9,6 +13,15 @@ pub(crate) trait FormatWrapper: std::fmt::Debug {
     type Format: 'static + ?Sized;
 
     const FORMAT: &'static Self::Format;
+
+    /// Format the value for sending over the wire.
+    #[cfg(any(feature = "client", feature = "server"))]
+    fn format_value(value: OffsetDateTime) -> Result<String, time::error::Format>
+    where
+        Self::Format: time::formatting::Formattable,
+    {
+        value.format(Self::FORMAT)
+    }
 }
 
 #[derive(Debug)]
@@ -18,8 +31,15 @@ impl FormatWrapper for Iso8601 {
     type Format = format_description::well_known::Iso8601;
 
     const FORMAT: &'static Self::Format = &Self::Format::DEFAULT;
+
+    // Only responses honour the server's timestamp precision; requests use the default one.
+    #[cfg(feature = "server")]
+    fn format_value(value: OffsetDateTime) -> Result<String, time::error::Format> {
+        crate::server::format_iso8601(value)
+    }
 }
 
+#[cfg_attr(not(feature = "camera"), allow(dead_code))]
 #[derive(Debug)]
 pub(crate) struct Fits;
 
@@ -31,6 +51,14 @@ impl FormatWrapper for Fits {
     );
 }
 
+/// Fallback format for timestamps produced by non-conforming devices.
+///
+/// Used after replacing a space separator with `T` and stripping a trailing `Z`,
+/// and accepts any number of fractional digits.
+#[cfg(any(feature = "client", feature = "server"))]
+const LENIENT_FORMAT: &[format_description::BorrowedFormatItem<'static>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second][optional [.[subsecond]]]");
+
 #[derive(Debug)]
 pub(crate) struct TimeRepr<F>(OffsetDateTime, PhantomData<F>);
 
@@ -46,20 +74,19 @@ impl<F> From<TimeRepr<F>> for SystemTime {
     }
 }
 
-#[cfg(feature = "server")]
+#[cfg(any(feature = "client", feature = "server"))]
 impl<F: FormatWrapper> serde::Serialize for TimeRepr<F>
 where
     F::Format: time::formatting::Formattable,
 {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        self.0
-            .format(F::FORMAT)
+        F::format_value(self.0)
             .map_err(serde::ser::Error::custom)?
             .serialize(serializer)
     }
 }
 
-#[cfg(feature = "client")]
+#[cfg(any(feature = "client", feature = "server"))]
 impl<'de, F: FormatWrapper> serde::Deserialize<'de> for TimeRepr<F>
 where
     F::Format: time::parsing::Parsable,
@@ -78,7 +105,13 @@ where
             }
 
             fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
-                match time::PrimitiveDateTime::parse(value, F::FORMAT) {
+                let parsed = time::PrimitiveDateTime::parse(value, F::FORMAT).or_else(|err| {
+                    let normalized = value.trim_end_matches(['Z', 'z']).replacen(' ', "T", 1);
+                    time::PrimitiveDateTime::parse(&normalized, LENIENT_FORMAT)
+                        .ok()
+                        .ok_or(err)
+                });
+                match parsed {
                     Ok(value) => Ok(TimeRepr(value.assume_utc(), PhantomData)),
                     Err(err) => Err(serde::de::Error::custom(err)),
                 }
@@ -88,3 +121,71 @@ where
         deserializer.deserialize_str(Visitor(PhantomData))
     }
 }
+
+#[cfg(all(test, any(feature = "client", feature = "server")))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn parse<F: FormatWrapper>(value: &str) -> SystemTime
+    where
+        F::Format: time::parsing::Parsable,
+    {
+        serde_json::from_value::<TimeRepr<F>>(value.into())
+            .expect("timestamp should be accepted")
+            .into()
+    }
+
+    #[test]
+    fn utc_date_round_trip() {
+        let value = SystemTime::now();
+        let serialized = serde_json::to_value(TimeRepr::<Iso8601>::from(value))
+            .expect("timestamp should be serializable");
+        let serialized = serialized.as_str().expect("timestamp should be a string");
+        let parsed = parse::<Iso8601>(serialized);
+
+        let diff = parsed
+            .duration_since(value)
+            .unwrap_or_else(|err| err.duration());
+        assert!(diff < Duration::from_millis(1), "{serialized}: {diff:?}");
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn precision_round_trip() {
+        use crate::server::TimestampPrecision;
+
+        let value = SystemTime::UNIX_EPOCH + Duration::from_nanos(1_234_400_000);
+
+        for (precision, expected) in [
+            (TimestampPrecision::Full, value),
+            (
+                TimestampPrecision::Milliseconds,
+                SystemTime::UNIX_EPOCH + Duration::from_millis(1_234),
+            ),
+        ] {
+            let serialized = precision
+                .scope(|| serde_json::to_value(TimeRepr::<Iso8601>::from(value)))
+                .expect("timestamp should be serializable");
+            let serialized = serialized.as_str().expect("timestamp should be a string");
+            assert_eq!(parse::<Iso8601>(serialized), expected, "{serialized}");
+        }
+    }
+
+    #[test]
+    fn lenient_parsing() {
+        let expected = SystemTime::UNIX_EPOCH + Duration::from_millis(1_500);
+
+        for value in [
+            "1970-01-01T00:00:01.500",
+            "1970-01-01T00:00:01.500Z",
+            "1970-01-01 00:00:01.500",
+            "1970-01-01T00:00:01.5000000",
+        ] {
+            assert_eq!(parse::<Fits>(value), expected, "{value}");
+        }
+        for value in ["1970-01-01T00:00:01.5Z", "1970-01-01 00:00:01.5"] {
+            assert_eq!(parse::<Iso8601>(value), expected, "{value}");
+        }
+    }
+}
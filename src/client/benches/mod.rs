@@ -1,4 +1,4 @@
-use super::Response;
+use super::{DecodeOptions, Response};
 use crate::api::{ConfiguredDevice, FallibleDeviceType, ImageArray};
 use crate::response::ValueResponse;
 use crate::ASCOMResult;
@@ -18,6 +18,7 @@ macro_rules! declare_parsing_benches {
                             <$ty>::from_reqwest(
                                 $mime,
                                 include_bytes!($fixture_path),
+                                DecodeOptions::default(),
                             )
                             .expect("Failed to parse fixture")
                         });
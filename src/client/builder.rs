@@ -0,0 +1,330 @@
+use super::{
+    Client, ClientMetrics, DecodeOptions, Parsing, PropertyThrottle, RawClient, RawResponse,
+    RequestHook, ResponseHook, RetryPolicy,
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{IntoUrl, RequestBuilder};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builder for a [`Client`] with custom settings.
+///
+/// Obtained via [`Client::builder`].
+#[derive(Clone, Default, custom_debug::Debug)]
+pub struct ClientBuilder {
+    client_id: Option<NonZeroU32>,
+    metrics: Option<Arc<ClientMetrics>>,
+    decode_options: DecodeOptions,
+    heartbeat_interval: Option<Duration>,
+    cache_supported_actions: bool,
+    timeout: Option<Duration>,
+    image_timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    #[debug(skip)]
+    request_hook: Option<RequestHook>,
+    #[debug(skip)]
+    response_hook: Option<ResponseHook>,
+    throttle_intervals: HashMap<String, Duration>,
+    span_level: Option<tracing::Level>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    // Might contain credentials, so keep them out of logs.
+    #[debug(skip)]
+    default_headers: HeaderMap,
+    /// HTTP client already built from the settings above, shared between clients.
+    #[debug(skip)]
+    http: Option<reqwest::Client>,
+    #[cfg(feature = "tls")]
+    #[debug(skip)]
+    root_certificates: Vec<reqwest::Certificate>,
+    #[cfg(feature = "tls")]
+    accept_invalid_certs: bool,
+}
+
+impl ClientBuilder {
+    /// Create a builder with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Identify this client to servers with the given `ClientID` instead of a random one.
+    ///
+    /// A stable ID lets servers and dashboards correlate requests from the same client
+    /// across restarts or multiple processes.
+    pub fn client_id(mut self, client_id: NonZeroU32) -> Self {
+        self.client_id = Some(client_id);
+        self
+    }
+
+    /// Record outcomes of calls made by devices from this client into the given metrics collection.
+    pub fn metrics(mut self, metrics: Arc<ClientMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Accept common deviations from the Alpaca specification in server responses.
+    ///
+    /// Some non-conforming servers send booleans as `"true"` or `1`, numbers as strings,
+    /// or field names in a different case (e.g. `errornumber` instead of `ErrorNumber`).
+    /// By default such responses are rejected; with lenient parsing enabled they're coerced
+    /// to the expected types instead.
+    pub fn lenient_parsing(mut self, lenient: bool) -> Self {
+        self.decode_options.parsing = if lenient {
+            Parsing::Lenient
+        } else {
+            Parsing::Strict
+        };
+        self
+    }
+
+    /// Limit the total number of pixels (including all planes) accepted in image responses.
+    ///
+    /// Dimensions claimed by the server are checked against this limit before allocating
+    /// the image, protecting against buggy or malicious servers. Defaults to 256 megapixels.
+    pub fn max_image_pixels(mut self, max_image_pixels: usize) -> Self {
+        self.decode_options.max_image_pixels = max_image_pixels;
+        self
+    }
+
+    /// Keep device connections alive by periodically reading their `connected` property.
+    ///
+    /// Each device retrieved via [`Client::get_devices`] will get its own background task that
+    /// polls the device every `interval` and stops once the device is dropped.
+    pub fn heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Cache each device's [`supported_actions`](crate::api::Device::supported_actions) after
+    /// the first request made by [`DeviceExt::has_action`](crate::api::DeviceExt::has_action).
+    ///
+    /// This saves a round-trip on every [`checked_action`](crate::api::DeviceExt::checked_action)
+    /// call, but won't pick up actions that only become available later, e.g. after connecting.
+    pub fn cache_supported_actions(mut self, cache: bool) -> Self {
+        self.cache_supported_actions = cache;
+        self
+    }
+
+    /// Set the level of the span created for each request.
+    ///
+    /// Defaults to [`Level::DEBUG`](tracing::Level::DEBUG).
+    pub fn span_level(mut self, level: tracing::Level) -> Self {
+        self.span_level = Some(level);
+        self
+    }
+
+    /// Fail requests that don't complete within `timeout`.
+    ///
+    /// Timed out calls return an error with a message starting with `Request timed out`,
+    /// so they can be told apart from errors reported by the device. By default requests
+    /// can take indefinitely long.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Use a separate timeout for image downloads, which can take much longer than other requests.
+    ///
+    /// Defaults to the general [`timeout`](Self::timeout).
+    pub fn image_timeout(mut self, timeout: Duration) -> Self {
+        self.image_timeout = Some(timeout);
+        self
+    }
+
+    /// Retry `GET` requests that fail due to transient network errors according to the given policy.
+    ///
+    /// By default failed requests are not retried.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Customize every request sent by this client and its devices right before it's sent.
+    ///
+    /// This is an escape hatch for servers that require nonstandard requests, e.g. custom
+    /// headers or extra query parameters that the typed API doesn't expose.
+    pub fn request_hook(
+        mut self,
+        hook: impl Fn(RequestBuilder) -> RequestBuilder + Send + Sync + 'static,
+    ) -> Self {
+        self.request_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Inspect every raw response received by this client and its devices before it's parsed.
+    ///
+    /// The hook is called for all responses, including HTTP errors and ones that then fail to parse,
+    /// which makes it useful for logging or dumping the exact output of misbehaving devices.
+    /// Responses reused due to [throttling](Self::throttle_property) are not passed to the hook again.
+    pub fn on_raw_response(
+        mut self,
+        hook: impl Fn(&RawResponse<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        self.response_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Read the given property from each device at most once per `min_interval`.
+    ///
+    /// Repeated reads within the interval return the previous response without contacting
+    /// the server. This protects devices with slow links, like mounts connected over serial ports,
+    /// from aggressive polling by UIs:
+    ///
+    /// ```no_run
+    /// # fn main() -> eyre::Result<()> {
+    /// use ascom_alpaca::Client;
+    /// use std::time::Duration;
+    ///
+    /// let client = Client::builder()
+    ///     .throttle_property("RightAscension", Duration::from_millis(500))
+    ///     .throttle_property("Declination", Duration::from_millis(500))
+    ///     .build("http://localhost:11111")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// `property` is the Alpaca method name as used in the URL, compared case-insensitively.
+    /// Reads with different parameters (e.g. of different switches) are cached separately.
+    ///
+    /// Reused responses are still recorded in [`metrics`](Self::metrics) like any other call,
+    /// with the outcome of the original response.
+    pub fn throttle_property(mut self, property: &str, min_interval: Duration) -> Self {
+        let _ = self
+            .throttle_intervals
+            .insert(property.to_ascii_lowercase(), min_interval);
+        self
+    }
+
+    /// Trust the given root certificate in addition to the system ones when connecting over HTTPS.
+    ///
+    /// This is useful for servers behind a reverse proxy that uses a certificate issued
+    /// by an internal CA.
+    #[cfg(feature = "tls")]
+    pub fn root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Accept any server certificate, including expired and self-signed ones.
+    ///
+    /// This disables protection against man-in-the-middle attacks, so it should only be
+    /// used during development.
+    #[cfg(feature = "tls")]
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Keep at most `max` idle connections open to each server host.
+    ///
+    /// By default the number of idle connections is unlimited.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Close connections that have been idle for longer than `timeout`.
+    ///
+    /// Defaults to 90 seconds. Consider lowering it when talking to many servers only briefly,
+    /// e.g. when querying every server found via discovery, so that their sockets are reaped sooner.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Send the given `User-Agent` header instead of the default `ascom-alpaca-rs`.
+    ///
+    /// This is useful for identifying traffic from different applications or subsystems
+    /// in gateway and firewall logs.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Send the given header with every request, e.g. an `Authorization` token required by a gateway.
+    ///
+    /// Setting the same header again replaces the previous value. Consider marking credentials
+    /// as [sensitive](HeaderValue::set_sensitive) so that they're not exposed in debug output.
+    pub fn default_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        let _ = self.default_headers.insert(name, value);
+        self
+    }
+
+    /// Build the HTTP client for these settings, or return `None` if the default one can be used.
+    fn build_http(&self) -> eyre::Result<Option<reqwest::Client>> {
+        if let Some(http) = &self.http {
+            return Ok(Some(http.clone()));
+        }
+        let mut custom = false;
+        let mut builder = super::reqwest_builder();
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+            custom = true;
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+            custom = true;
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+            custom = true;
+        }
+        if !self.default_headers.is_empty() {
+            builder = builder.default_headers(self.default_headers.clone());
+            custom = true;
+        }
+        #[cfg(feature = "tls")]
+        if self.accept_invalid_certs || !self.root_certificates.is_empty() {
+            builder = self
+                .root_certificates
+                .iter()
+                .cloned()
+                .fold(builder, reqwest::ClientBuilder::add_root_certificate)
+                .danger_accept_invalid_certs(self.accept_invalid_certs);
+            custom = true;
+        }
+        Ok(custom.then(|| builder.build()).transpose()?)
+    }
+
+    /// Build the HTTP client once so that all clients built from this builder share its connection pool.
+    pub(crate) fn share_http(mut self) -> eyre::Result<Self> {
+        self.http = self.build_http()?;
+        Ok(self)
+    }
+
+    /// Build a client for the given server URL.
+    pub fn build(self, base_url: impl IntoUrl) -> eyre::Result<Client> {
+        let mut inner = RawClient::new(base_url.into_url()?)?;
+        if let Some(http) = self.build_http()? {
+            inner.http = http;
+        }
+        if let Some(client_id) = self.client_id {
+            inner.client_id = client_id;
+        }
+        inner.metrics = self.metrics;
+        inner.decode_options = self.decode_options;
+        inner.heartbeat_interval = self.heartbeat_interval;
+        inner.cache_supported_actions = self.cache_supported_actions;
+        inner.timeout = self.timeout;
+        inner.image_timeout = self.image_timeout;
+        inner.retry_policy = self.retry_policy;
+        if let Some(span_level) = self.span_level {
+            inner.span_level = span_level;
+        }
+        inner.request_hook = self.request_hook;
+        inner.response_hook = self.response_hook;
+        inner.throttle = (!self.throttle_intervals.is_empty())
+            .then(|| Arc::new(PropertyThrottle::new(self.throttle_intervals)));
+        Ok(Client::from_raw(inner))
+    }
+
+    /// Build a client for the given server address.
+    pub fn build_from_addr(self, addr: impl Into<SocketAddr>) -> Client {
+        self.build(format!("http://{}/", addr.into()))
+            .expect("creating client from an address should always succeed")
+    }
+}
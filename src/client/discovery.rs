@@ -1,16 +1,41 @@
 use crate::api::TypedDevice;
 use crate::discovery::{
-    bind_socket, get_active_interfaces, AlpacaPort, DEFAULT_DISCOVERY_PORT, DISCOVERY_ADDR_V6,
-    DISCOVERY_MSG,
+    bind_socket, discovery_msg, get_active_interfaces, AlpacaPort, DEFAULT_DISCOVERY_PORT,
+    DEFAULT_PROTOCOL_VERSIONS, DISCOVERY_ADDR_V6,
 };
+use crate::ClientBuilder;
 use futures::StreamExt;
 use netdev::interface::InterfaceType;
 use netdev::Interface;
+use serde::Deserialize;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::time::Duration;
 use tokio::net::UdpSocket;
+use tokio::time::Instant;
 use tracing_futures::Instrument;
 
+/// IP protocol versions to send discovery requests over.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveryProtocol {
+    /// Only send IPv4 broadcast requests.
+    V4,
+    /// Only send IPv6 multicast requests.
+    V6,
+    /// Send both IPv4 broadcast and IPv6 multicast requests.
+    #[default]
+    Both,
+}
+
+impl DiscoveryProtocol {
+    const fn includes_v4(self) -> bool {
+        matches!(self, Self::V4 | Self::Both)
+    }
+
+    const fn includes_v6(self) -> bool {
+        matches!(self, Self::V6 | Self::Both)
+    }
+}
+
 /// Discovery client.
 #[derive(Debug, Clone, Copy)]
 pub struct Client {
@@ -18,7 +43,11 @@ pub struct Client {
     ///
     /// Defaults to 2.
     pub num_requests: usize,
-    /// Time to wait after each discovered device for more responses.
+    /// Time to wait for responses after each discovery request.
+    ///
+    /// The wait is extended by the same amount whenever a new server is discovered. Duplicate
+    /// or malformed responses don't extend it, so discovery always ends after at most
+    /// [`num_requests`](Self::num_requests) timeouts without new servers.
     ///
     /// Defaults to 1 second.
     pub timeout: Duration,
@@ -26,6 +55,51 @@ pub struct Client {
     ///
     /// Defaults to 32227.
     pub discovery_port: u16,
+    /// Maximum number of discovered servers to query for their devices at the same time.
+    ///
+    /// Defaults to 16.
+    pub max_concurrent_requests: usize,
+    /// Discovery protocol versions to send requests for.
+    ///
+    /// A separate request is sent for each version, and responses to all of them are collected.
+    /// Defaults to version 1 only.
+    pub protocol_versions: &'static [u32],
+    /// IP protocol versions to send discovery requests over.
+    ///
+    /// Responses to all of them are merged into the same stream.
+    /// Defaults to [`DiscoveryProtocol::Both`].
+    pub protocol: DiscoveryProtocol,
+}
+
+/// Response from an Alpaca server to a discovery request.
+///
+/// Returned by [`BoundClient::discover_servers`] for applications that want to implement
+/// their own filtering or ranking of discovered servers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredServer {
+    /// Address the discovery response was received from.
+    pub responder_addr: SocketAddr,
+    /// Port of the Alpaca API advertised in the response.
+    pub alpaca_port: u16,
+    /// Any fields in the response other than `AlpacaPort`.
+    ///
+    /// These are not defined by the Alpaca specification, but some servers include them anyway.
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DiscoveredServer {
+    /// Address of the Alpaca API, suitable for [`crate::Client::new_from_addr`].
+    pub fn alpaca_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.responder_addr.ip(), self.alpaca_port)
+    }
+}
+
+#[derive(Deserialize)]
+struct DiscoveryResponse {
+    #[serde(flatten)]
+    port: AlpacaPort,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Bound discovery client ready to send discovery requests.
@@ -47,11 +121,16 @@ impl BoundClient {
             if addr.is_multicast() {
                 socket2::SockRef::from(&self.socket).set_multicast_if_v6(intf.index)?;
             }
-            // UDP packets are sent as whole messages, no need to check length.
-            let _ = self
-                .socket
-                .send_to(DISCOVERY_MSG, (addr, self.client.discovery_port))
-                .await?;
+            for &version in self.client.protocol_versions {
+                // UDP packets are sent as whole messages, no need to check length.
+                let _ = self
+                    .socket
+                    .send_to(
+                        discovery_msg(version).as_bytes(),
+                        (addr, self.client.discovery_port),
+                    )
+                    .await?;
+            }
             Ok::<_, std::io::Error>(())
         };
         match send_op.await {
@@ -63,14 +142,16 @@ impl BoundClient {
     #[tracing::instrument(level = "debug", skip_all)]
     async fn send_discovery_msgs(&self) {
         for intf in &self.interfaces {
-            for net in &intf.ipv4 {
-                let broadcast = Ipv4Addr::from(u32::from(net.addr) | !u32::from(net.netmask));
+            if self.client.protocol.includes_v4() {
+                for net in &intf.ipv4 {
+                    let broadcast = Ipv4Addr::from(u32::from(net.addr) | !u32::from(net.netmask));
 
-                self.send_discovery_msg(broadcast.to_ipv6_mapped(), intf)
-                    .await;
+                    self.send_discovery_msg(broadcast.to_ipv6_mapped(), intf)
+                        .await;
+                }
             }
 
-            if !intf.ipv6.is_empty() {
+            if self.client.protocol.includes_v6() && !intf.ipv6.is_empty() {
                 self.send_discovery_msg(
                     if intf.if_type == InterfaceType::Loopback {
                         // Loopback interface doesn't have a link-local address
@@ -87,10 +168,13 @@ impl BoundClient {
     }
 
     #[tracing::instrument(level = "debug", ret, err(level = "warn"), skip_all)]
-    async fn recv_discovery_response(&mut self) -> eyre::Result<SocketAddr> {
+    async fn recv_discovery_response(&mut self) -> eyre::Result<DiscoveredServer> {
         self.buf.clear();
         let (len, addr) = self.socket.recv_buf_from(&mut self.buf).await?;
-        let AlpacaPort { alpaca_port } = serde_json::from_slice(&self.buf[..len])?;
+        let DiscoveryResponse {
+            port: AlpacaPort { alpaca_port },
+            extra,
+        } = serde_json::from_slice(&self.buf[..len])?;
         let ip = match addr.ip() {
             IpAddr::V6(ip) => ip,
             IpAddr::V4(_) => unreachable!(
@@ -100,33 +184,52 @@ impl BoundClient {
         // We used IPv6 socket to send IPv4 requests as well by using mapped addresses;
         // now that we got responses, we need to remap them back to IPv4.
         let ip = ip.to_ipv4_mapped().map_or(IpAddr::V6(ip), IpAddr::V4);
-        Ok(SocketAddr::new(ip, alpaca_port))
+        Ok(DiscoveredServer {
+            responder_addr: SocketAddr::new(ip, addr.port()),
+            alpaca_port,
+            extra,
+        })
     }
 
-    /// Discover Alpaca servers on the local network.
+    /// Discover Alpaca servers on the local network, returning their full discovery responses.
     ///
-    /// This function returns a stream of discovered device addresses.
-    pub fn discover_addrs(&mut self) -> impl '_ + futures::Stream<Item = SocketAddr> {
+    /// Responses are deduplicated by [`DiscoveredServer::alpaca_addr`].
+    pub fn discover_servers(&mut self) -> impl '_ + futures::Stream<Item = DiscoveredServer> {
         async_fn_stream::fn_stream(|emitter| async move {
             self.seen.clear();
 
             for _ in 0..self.client.num_requests {
                 self.send_discovery_msgs().await;
 
+                let mut deadline = Instant::now() + self.client.timeout;
                 while let Ok(result) =
-                    tokio::time::timeout(self.client.timeout, self.recv_discovery_response()).await
+                    tokio::time::timeout_at(deadline, self.recv_discovery_response()).await
                 {
                     match result {
-                        Ok(addr) if !self.seen.contains(&addr) => {
-                            self.seen.push(addr);
-                            emitter.emit(addr).await;
+                        Ok(server) if !self.seen.contains(&server.alpaca_addr()) => {
+                            self.seen.push(server.alpaca_addr());
+                            emitter.emit(server).await;
+                            deadline = Instant::now() + self.client.timeout;
                         }
                         _ => {}
                     }
                 }
             }
         })
-        .instrument(tracing::error_span!("discover_addrs"))
+        .instrument(tracing::error_span!("discover_servers"))
+    }
+
+    /// Discover Alpaca servers on the local network.
+    ///
+    /// This function returns a stream of discovered device addresses.
+    ///
+    /// Like with [`Self::discover_servers`], each address is returned only once per call even if
+    /// it replied on several network interfaces or to several requests. A server reachable via
+    /// both IPv4 and IPv6 is still returned once for each, as those are different addresses.
+    pub fn discover_addrs(&mut self) -> impl '_ + futures::Stream<Item = SocketAddr> {
+        self.discover_servers()
+            .map(|server| server.alpaca_addr())
+            .instrument(tracing::error_span!("discover_addrs"))
     }
 
     /// Discover all devices on the local network.
@@ -138,19 +241,45 @@ impl BoundClient {
     ///
     /// This function will log but otherwise ignore errors from discovered but unreachable servers.
     /// If you need more control, use [`Self::discover_addrs`] and [`crate::Client::new_from_addr`] directly instead.
+    ///
+    /// Up to [`Client::max_concurrent_requests`] servers are queried for their devices at the same time.
     pub fn discover_devices(&mut self) -> impl '_ + futures::Stream<Item = TypedDevice> {
-        self.discover_addrs()
-            .filter_map(|addr| async move {
-                match crate::Client::new_from_addr(addr).get_devices().await {
-                    Ok(devices) => Some(devices),
-                    Err(err) => {
-                        tracing::warn!(%addr, %err, "failed to retrieve list of devices");
-                        None
+        self.discover_devices_with(ClientBuilder::default())
+            .expect("default client settings should always be valid")
+    }
+
+    /// Discover all devices on the local network, using clients with the given settings.
+    ///
+    /// All returned devices share a single connection pool configured by `client_builder`,
+    /// e.g. via [`ClientBuilder::pool_idle_timeout`] to close connections to servers that
+    /// aren't used after discovery sooner.
+    ///
+    /// See [`Self::discover_devices`] for details.
+    pub fn discover_devices_with(
+        &mut self,
+        client_builder: ClientBuilder,
+    ) -> eyre::Result<impl '_ + futures::Stream<Item = TypedDevice>> {
+        let max_concurrent_requests = self.client.max_concurrent_requests.max(1);
+        let client_builder = client_builder.share_http()?;
+
+        Ok(self
+            .discover_addrs()
+            .map(move |addr| {
+                let client = client_builder.clone().build_from_addr(addr);
+                async move {
+                    match client.get_devices().await {
+                        Ok(devices) => Some(devices),
+                        Err(err) => {
+                            tracing::warn!(%addr, %err, "failed to retrieve list of devices");
+                            None
+                        }
                     }
                 }
             })
-            .flat_map_unordered(None, futures::stream::iter)
-            .instrument(tracing::error_span!("discover_devices"))
+            .buffer_unordered(max_concurrent_requests)
+            .filter_map(futures::future::ready)
+            .flat_map(futures::stream::iter)
+            .instrument(tracing::error_span!("discover_devices")))
     }
 }
 
@@ -161,6 +290,9 @@ impl Client {
             num_requests: 2,
             timeout: Duration::from_secs(1),
             discovery_port: DEFAULT_DISCOVERY_PORT,
+            max_concurrent_requests: 16,
+            protocol_versions: DEFAULT_PROTOCOL_VERSIONS,
+            protocol: DiscoveryProtocol::Both,
         }
     }
 
@@ -168,6 +300,13 @@ impl Client {
     #[tracing::instrument(level = "error")]
     pub async fn bind(self) -> eyre::Result<BoundClient> {
         let socket = bind_socket((Ipv6Addr::UNSPECIFIED, 0)).await?;
+        if self.protocol.includes_v4() {
+            // Sending to IPv4 broadcast addresses is rejected without this option,
+            // even when they're mapped into IPv6.
+            if let Err(err) = socket2::SockRef::from(&socket).set_broadcast(true) {
+                tracing::warn!(%err, "Couldn't enable IPv4 broadcast on the discovery socket");
+            }
+        }
         let interfaces = tokio::task::spawn_blocking(|| get_active_interfaces().collect()).await?;
         Ok(BoundClient {
             client: self,
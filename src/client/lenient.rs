@@ -0,0 +1,201 @@
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, IntoDeserializer, Visitor};
+use serde::{forward_to_deserialize_any, Deserializer};
+use serde_json::{Number, Value};
+
+/// How JSON responses should be parsed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Parsing {
+    /// Require responses to match the Alpaca specification exactly.
+    #[default]
+    Strict,
+    /// Accept common deviations like numbers and booleans sent as strings, booleans sent as `0` / `1`,
+    /// or field names in a different case.
+    Lenient,
+}
+
+impl Parsing {
+    pub(crate) fn from_slice<T: DeserializeOwned>(self, bytes: &[u8]) -> serde_json::Result<T> {
+        match serde_json::from_slice(bytes) {
+            Err(err) if self == Self::Lenient => {
+                tracing::debug!(%err, "Strict parsing failed, retrying in lenient mode");
+                T::deserialize(Lenient(serde_json::from_slice(bytes)?))
+            }
+            result => result,
+        }
+    }
+}
+
+/// JSON value deserializer that coerces mismatched scalars into the types requested by the visitor.
+struct Lenient(Value);
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for Lenient {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            match self.0 {
+                Value::String(s) => match s.trim().parse::<Number>() {
+                    Ok(number) => Value::Number(number).$method(visitor),
+                    Err(_) => Value::String(s).$method(visitor),
+                },
+                Value::Bool(b) => Value::from(u8::from(b)).$method(visitor),
+                value => Self(value).deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(items) => {
+                let mut seq = SeqDeserializer::new(items.into_iter().map(Self));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Object(map) => {
+                let mut map =
+                    MapDeserializer::new(map.into_iter().map(|(key, value)| (key, Self(value))));
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let b = match &self.0 {
+            Value::String(s) => match s.trim() {
+                s if s.eq_ignore_ascii_case("true") || s == "1" => true,
+                s if s.eq_ignore_ascii_case("false") || s == "0" => false,
+                _ => return self.0.deserialize_bool(visitor),
+            },
+            Value::Number(n) => match n.as_u64() {
+                Some(0) => false,
+                Some(1) => true,
+                _ => return self.0.deserialize_bool(visitor),
+            },
+            _ => return self.0.deserialize_bool(visitor),
+        };
+        visitor.visit_bool(b)
+    }
+
+    deserialize_number! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Self(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            // Field names are case-insensitive per the Alpaca specification,
+            // so map them onto the expected casing.
+            Value::Object(map) => Self(Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| {
+                        let key = fields
+                            .iter()
+                            .find(|field| field.eq_ignore_ascii_case(&key))
+                            .map_or(key, |&field| field.to_owned());
+                        (key, value)
+                    })
+                    .collect(),
+            ))
+            .deserialize_any(visitor),
+            value => Self(value).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map identifier ignored_any i128 u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parsing;
+    use crate::response::ValueResponse;
+    use crate::{ASCOMError, ASCOMErrorCode};
+
+    #[test]
+    fn strict_rejects_deviations() {
+        assert!(Parsing::Strict
+            .from_slice::<ValueResponse<bool>>(br#"{"Value":"true"}"#)
+            .is_err());
+    }
+
+    #[test]
+    fn lenient_accepts_deviations() {
+        let parse_bool = |json: &[u8]| {
+            Parsing::Lenient
+                .from_slice::<ValueResponse<bool>>(json)
+                .map(|r| r.value)
+                .ok()
+        };
+        assert_eq!(parse_bool(br#"{"Value":"True"}"#), Some(true));
+        assert_eq!(parse_bool(br#"{"Value":0}"#), Some(false));
+        assert_eq!(parse_bool(br#"{"Value":"maybe"}"#), None);
+
+        let parse_vec = |json: &[u8]| {
+            Parsing::Lenient
+                .from_slice::<ValueResponse<Vec<f64>>>(json)
+                .map(|r| r.value)
+                .ok()
+        };
+        assert_eq!(parse_vec(br#"{"Value":["1.5", 2]}"#), Some(vec![1.5, 2.0]));
+    }
+
+    #[test]
+    fn lenient_accepts_field_case_mismatch() {
+        let json = br#"{"errornumber":1024,"ERRORMESSAGE":"nope"}"#;
+
+        assert!(Parsing::Strict.from_slice::<ASCOMError>(json).is_err());
+
+        let error = Parsing::Lenient
+            .from_slice::<ASCOMError>(json)
+            .expect("lenient parsing should accept mismatched field case");
+        assert_eq!(error.code, ASCOMErrorCode::NOT_IMPLEMENTED);
+        assert_eq!(error.message, "nope");
+    }
+}
@@ -0,0 +1,180 @@
+use crate::api::DeviceType;
+use crate::{ASCOMErrorCode, ASCOMResult};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write};
+use std::sync::{Mutex, PoisonError};
+
+/// Outcome of a single device call as recorded by [`ClientMetrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CallOutcome {
+    /// The server responded with the given ASCOM error code ([`ASCOMErrorCode::OK`] on success).
+    Ascom(ASCOMErrorCode),
+    /// The server responded with a non-successful HTTP status code.
+    HttpStatus(u16),
+    /// The request failed without a valid response (connection error, malformed body, etc.).
+    Transport,
+}
+
+impl CallOutcome {
+    pub(crate) fn of<T>(result: &eyre::Result<ASCOMResult<T>>) -> Self {
+        match result {
+            Ok(Ok(_)) => Self::Ascom(ASCOMErrorCode::OK),
+            Ok(Err(err)) => Self::Ascom(err.code),
+            Err(err) => err
+                .downcast_ref::<reqwest::Error>()
+                .and_then(reqwest::Error::status)
+                .map_or(Self::Transport, |status| Self::HttpStatus(status.as_u16())),
+        }
+    }
+}
+
+impl Display for CallOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ascom(code) => Display::fmt(code, f),
+            Self::HttpStatus(status) => write!(f, "HTTP_{status}"),
+            Self::Transport => f.write_str("TRANSPORT"),
+        }
+    }
+}
+
+/// A single counter from [`ClientMetrics::snapshot`].
+#[derive(Debug, Clone)]
+pub struct CallMetric {
+    /// Device type, e.g. `Camera`.
+    pub device_type: String,
+    /// Alpaca method path, e.g. `imageready`.
+    pub action: &'static str,
+    /// Outcome of the calls.
+    pub outcome: CallOutcome,
+    /// Number of calls with this outcome.
+    pub count: u64,
+}
+
+/// Per-device-type, per-action and per-outcome call counters collected by the client.
+///
+/// Create one, wrap it in an [`Arc`](std::sync::Arc) and pass it to
+/// [`ClientBuilder::metrics`](crate::ClientBuilder::metrics). The same instance
+/// can be shared between several clients to get aggregated numbers.
+#[derive(Debug, Default)]
+pub struct ClientMetrics {
+    counters: Mutex<BTreeMap<(DeviceType, &'static str, CallOutcome), u64>>,
+}
+
+impl ClientMetrics {
+    /// Create a new empty metrics collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record(
+        &self,
+        device_type: DeviceType,
+        action: &'static str,
+        outcome: CallOutcome,
+    ) {
+        *self
+            .counters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry((device_type, action, outcome))
+            .or_default() += 1;
+    }
+
+    /// Get a copy of all the counters collected so far.
+    pub fn snapshot(&self) -> Vec<CallMetric> {
+        self.counters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(&(device_type, action, outcome), &count)| CallMetric {
+                device_type: device_type.to_string(),
+                action,
+                outcome,
+                count,
+            })
+            .collect()
+    }
+
+    /// Render the counters in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let mut output = String::from(
+            "# HELP alpaca_client_calls_total Number of Alpaca device calls by outcome.\n\
+             # TYPE alpaca_client_calls_total counter\n",
+        );
+        for metric in self.snapshot() {
+            let _ = writeln!(
+                output,
+                r#"alpaca_client_calls_total{{device_type="{}",action="{}",outcome="{}"}} {}"#,
+                metric.device_type, metric.action, metric.outcome, metric.count,
+            );
+        }
+        output
+    }
+}
+
+// Building a `reqwest::Error` with a status requires an `http::Response`, which is only
+// available as a dependency of the server.
+#[cfg(all(test, feature = "server", feature = "camera"))]
+mod tests {
+    use super::*;
+    use crate::ASCOMError;
+
+    fn status_error(status: u16) -> reqwest::Error {
+        let response = http::Response::builder()
+            .status(status)
+            .body(String::new())
+            .expect("response should be valid");
+        reqwest::Response::from(response)
+            .error_for_status()
+            .expect_err("status should be an error")
+    }
+
+    #[test]
+    fn outcomes() {
+        assert_eq!(
+            CallOutcome::of::<()>(&Ok(Ok(()))),
+            CallOutcome::Ascom(ASCOMErrorCode::OK)
+        );
+        assert_eq!(
+            CallOutcome::of::<()>(&Ok(Err(ASCOMError::NOT_IMPLEMENTED))),
+            CallOutcome::Ascom(ASCOMErrorCode::NOT_IMPLEMENTED)
+        );
+        assert_eq!(
+            CallOutcome::of::<()>(&Err(eyre::eyre!("connection refused"))),
+            CallOutcome::Transport
+        );
+        // Status errors are wrapped with the response body, but should still be classified.
+        let err = eyre::Report::new(status_error(400))
+            .wrap_err("Server returned 400 Bad Request: unknown action");
+        assert_eq!(
+            CallOutcome::of::<()>(&Err(err)),
+            CallOutcome::HttpStatus(400)
+        );
+    }
+
+    #[test]
+    fn prometheus_format() {
+        let metrics = ClientMetrics::new();
+        metrics.record(
+            DeviceType::Camera,
+            "imageready",
+            CallOutcome::Ascom(ASCOMErrorCode::OK),
+        );
+        metrics.record(
+            DeviceType::Camera,
+            "imageready",
+            CallOutcome::Ascom(ASCOMErrorCode::OK),
+        );
+        metrics.record(DeviceType::Camera, "connect", CallOutcome::HttpStatus(400));
+
+        let output = metrics.render_prometheus();
+        assert!(output.starts_with("# HELP alpaca_client_calls_total"));
+        assert!(output.contains(
+            r#"alpaca_client_calls_total{device_type="Camera",action="imageready",outcome="OK"} 2"#
+        ));
+        assert!(output.contains(
+            r#"alpaca_client_calls_total{device_type="Camera",action="connect",outcome="HTTP_400"} 1"#
+        ));
+    }
+}
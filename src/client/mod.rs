@@ -4,15 +4,37 @@ mod benches;
 pub use benches::benches;
 
 mod discovery;
-pub use discovery::{BoundClient as BoundDiscoveryClient, Client as DiscoveryClient};
+pub use discovery::{
+    BoundClient as BoundDiscoveryClient, Client as DiscoveryClient, DiscoveredServer,
+    DiscoveryProtocol,
+};
 
 mod transaction;
 pub(crate) use transaction::*;
 
 mod response;
-pub(crate) use response::Response;
+pub(crate) use response::{DecodeOptions, Response, StreamDecoder};
 
-use crate::api::{ConfiguredDevice, DevicePath, FallibleDeviceType, ServerInfo, TypedDevice};
+mod lenient;
+pub(crate) use lenient::Parsing;
+
+mod metrics;
+pub use metrics::{CallMetric, CallOutcome, ClientMetrics};
+
+mod builder;
+pub use builder::ClientBuilder;
+
+mod retry;
+pub use retry::RetryPolicy;
+
+mod throttle;
+use throttle::PropertyThrottle;
+
+use crate::api::{
+    ConfiguredDevice, Device, DeviceDescriptor, DevicePath, DeviceType, FallibleDeviceType,
+    ServerInfo, TypedDevice,
+};
+use crate::macros::dynamic_span;
 use crate::params::{Action, ActionParams, Method};
 use crate::response::ValueResponse;
 use crate::{ASCOMError, ASCOMResult};
@@ -20,18 +42,27 @@ use eyre::ContextCompat;
 use mime::Mime;
 use reqwest::header::CONTENT_TYPE;
 use reqwest::{IntoUrl, RequestBuilder};
-use serde::Serialize;
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "camera")]
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::net::SocketAddr;
 use std::num::NonZeroU32;
-use std::sync::{Arc, LazyLock};
+use std::sync::{Arc, LazyLock, OnceLock};
+#[cfg(feature = "camera")]
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
 use tracing::Instrument;
 
 #[derive(Debug)]
 pub(crate) struct RawDeviceClient {
     pub(crate) inner: RawClient,
+    pub(crate) device_type: DeviceType,
     pub(crate) name: String,
     pub(crate) unique_id: String,
+    /// Cached `supported_actions` response, if enabled via [`ClientBuilder::cache_supported_actions`].
+    pub(crate) supported_actions_cache: Option<OnceLock<Vec<String>>>,
 }
 
 impl RawDeviceClient {
@@ -39,16 +70,54 @@ impl RawDeviceClient {
     where
         ASCOMResult<Resp>: Response,
     {
-        self.inner
-            .request::<ASCOMResult<Resp>>(action.into_parts())
-            .await
-            .unwrap_or_else(|err| Err(ASCOMError::unspecified(err)))
+        let params = action.into_parts();
+        let action = params.action;
+
+        let result = self.inner.request::<ASCOMResult<Resp>>(params).await;
+
+        if let Some(metrics) = &self.inner.metrics {
+            metrics.record(self.device_type, action, CallOutcome::of(&result));
+        }
+
+        result.unwrap_or_else(|err| Err(ASCOMError::unspecified(err)))
+    }
+
+    /// Periodically poll `connected` for as long as the device client is alive.
+    fn spawn_heartbeat(self: &Arc<Self>, runtime: &tokio::runtime::Handle, interval: Duration) {
+        let device = Arc::downgrade(self);
+
+        drop(
+            runtime.spawn(
+                async move {
+                    let mut ticker = tokio::time::interval(interval);
+                    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    // The first tick completes immediately.
+                    let _ = ticker.tick().await;
+
+                    loop {
+                        let _ = ticker.tick().await;
+
+                        let Some(device) = device.upgrade() else {
+                            break;
+                        };
+
+                        if let Err(err) = device.connected().await {
+                            tracing::warn!(%err, "Heartbeat failed");
+                        }
+                    }
+                }
+                .instrument(tracing::debug_span!("heartbeat", device = self.name)),
+            ),
+        );
     }
 }
 
+pub(crate) fn reqwest_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder().user_agent("ascom-alpaca-rs")
+}
+
 pub(crate) static REQWEST: LazyLock<reqwest::Client> = LazyLock::new(|| {
-    reqwest::Client::builder()
-        .user_agent("ascom-alpaca-rs")
+    reqwest_builder()
         .build()
         .expect("failed to create reqwest client")
 });
@@ -57,7 +126,43 @@ pub(crate) static REQWEST: LazyLock<reqwest::Client> = LazyLock::new(|| {
 pub(crate) struct RawClient {
     #[debug(format = r#""{}""#)]
     pub(crate) base_url: reqwest::Url,
+    #[debug(skip)]
+    pub(crate) http: reqwest::Client,
     pub(crate) client_id: NonZeroU32,
+    pub(crate) metrics: Option<Arc<ClientMetrics>>,
+    pub(crate) decode_options: DecodeOptions,
+    pub(crate) heartbeat_interval: Option<Duration>,
+    pub(crate) cache_supported_actions: bool,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) image_timeout: Option<Duration>,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    #[debug(skip)]
+    pub(crate) request_hook: Option<RequestHook>,
+    #[debug(skip)]
+    pub(crate) response_hook: Option<ResponseHook>,
+    pub(crate) throttle: Option<Arc<PropertyThrottle>>,
+    pub(crate) span_level: tracing::Level,
+}
+
+/// User-provided function to customize outgoing requests.
+pub(crate) type RequestHook = Arc<dyn Fn(RequestBuilder) -> RequestBuilder + Send + Sync>;
+
+/// User-provided function to inspect raw responses.
+pub(crate) type ResponseHook = Arc<dyn Fn(&RawResponse<'_>) + Send + Sync>;
+
+/// Raw HTTP response as received from the server, before any parsing.
+///
+/// Passed to the hook registered via [`ClientBuilder::on_raw_response`].
+#[derive(Debug, Clone, Copy)]
+pub struct RawResponse<'a> {
+    /// HTTP method of the request.
+    pub method: &'a reqwest::Method,
+    /// Full URL of the request, including query parameters.
+    pub url: &'a reqwest::Url,
+    /// HTTP status of the response.
+    pub status: reqwest::StatusCode,
+    /// Response body.
+    pub body: &'a [u8],
 }
 
 impl RawClient {
@@ -69,7 +174,19 @@ impl RawClient {
         );
         Ok(Self {
             base_url,
+            http: REQWEST.clone(),
             client_id: rand::random(),
+            metrics: None,
+            decode_options: DecodeOptions::default(),
+            heartbeat_interval: None,
+            cache_supported_actions: false,
+            timeout: None,
+            image_timeout: None,
+            retry_policy: None,
+            span_level: tracing::Level::DEBUG,
+            request_hook: None,
+            response_hook: None,
+            throttle: None,
         })
     }
 
@@ -79,11 +196,51 @@ impl RawClient {
             action,
             method,
             params,
-        }: ActionParams<impl Serialize + Send>,
+        }: ActionParams<impl Serialize + Send + Sync>,
+    ) -> eyre::Result<Resp> {
+        self.request_at(action, method, &params).await
+    }
+
+    /// Send a request to the given path relative to the base URL, retrying if configured.
+    pub(crate) async fn request_at<Resp: Response>(
+        &self,
+        action: &str,
+        method: Method,
+        params: &(impl Serialize + Sync),
     ) -> eyre::Result<Resp> {
+        let retry_policy = match method {
+            Method::Get => self.retry_policy,
+            Method::Put => None,
+        };
+        let mut retry = 0;
+        loop {
+            let result = self.request_once(action, method, params).await;
+            let Some(policy) = retry_policy else {
+                return result;
+            };
+            match result {
+                Err(err) if retry < policy.max_retries && RetryPolicy::is_transient(&err) => {
+                    let delay = policy.delay(retry);
+                    retry += 1;
+                    tracing::warn!(action, attempt = retry, %err, ?delay, "Retrying failed request");
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn request_once<Resp: Response>(
+        &self,
+        action: &str,
+        method: Method,
+        params: &(impl Serialize + Sync),
+    ) -> eyre::Result<Resp> {
+        // Each attempt gets its own transaction ID so that retries aren't mistaken for duplicates.
         let request_transaction = RequestTransaction::new(self.client_id);
 
-        let span = tracing::error_span!(
+        let span = dynamic_span!(
+            self.span_level,
             "Alpaca transaction",
             action,
             client_transaction_id = request_transaction.client_transaction_id,
@@ -91,9 +248,27 @@ impl RawClient {
         );
 
         async move {
-            tracing::debug!(?method, params = ?serdebug::debug(&params), base_url = %self.base_url, "Sending request");
+            let url = self.base_url.join(action)?;
+
+            let throttle = match (method, &self.throttle) {
+                (Method::Get, Some(throttle)) => throttle.interval(action).map(|interval| {
+                    let key = format!("{url} {:?}", serdebug::debug(params));
+                    (throttle, interval, key)
+                }),
+                _ => None,
+            };
+            if let Some((throttle, interval, key)) = &throttle {
+                if let Some((mime_type, bytes)) = throttle.get(key, *interval) {
+                    tracing::debug!("Reusing recent response for throttled property");
+                    return Ok(
+                        Resp::from_reqwest(mime_type, &bytes, self.decode_options)?.response,
+                    );
+                }
+            }
+
+            tracing::debug!(?method, params = ?serdebug::debug(params), base_url = %self.base_url, "Sending request");
 
-            let mut request = REQWEST.request(method.into(), self.base_url.join(action)?);
+            let mut request = self.http.request(method.into(), url);
 
             let add_params = match method {
                 Method::Get => RequestBuilder::query,
@@ -109,18 +284,82 @@ impl RawClient {
 
             request = Resp::prepare_reqwest(request);
 
-            let response = request.send().await?.error_for_status()?;
-            let mime_type = response
-                .headers()
-                .get(CONTENT_TYPE)
-                .context("Missing Content-Type header")?
-                .to_str()?
-                .parse::<Mime>()?;
-            let bytes = response.bytes().await?;
+            // Image downloads can legitimately take much longer than other requests.
+            let timeout = if action.starts_with("imagearray") {
+                self.image_timeout.or(self.timeout)
+            } else {
+                self.timeout
+            };
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+            let check_timeout = |err: reqwest::Error| match timeout {
+                Some(timeout) if err.is_timeout() => {
+                    eyre::Report::new(err).wrap_err(format!("Request timed out after {timeout:?}"))
+                }
+                _ => err.into(),
+            };
+
+            if let Some(request_hook) = &self.request_hook {
+                request = request_hook(request);
+            }
+
+            let mut response = request.send().await.map_err(check_timeout)?;
+            let status = response.status();
+            let status_error = response.error_for_status_ref().err();
+            let content_type = response.headers().get(CONTENT_TYPE).cloned();
+
+            // Decode large bodies as they arrive when nothing else needs to see them in full,
+            // so that they don't have to be held in memory twice.
+            let stream_decoder = match (&self.response_hook, &throttle, &status_error) {
+                (None, None, None) => content_type
+                    .as_ref()
+                    .and_then(|content_type| content_type.to_str().ok()?.parse::<Mime>().ok())
+                    .and_then(|mime_type| Resp::stream_decoder(&mime_type, self.decode_options)),
+                _ => None,
+            };
+
             let ResponseWithTransaction {
                 transaction: response_transaction,
                 response,
-            } = Resp::from_reqwest(mime_type, &bytes)?;
+            } = if let Some(mut decoder) = stream_decoder {
+                while let Some(chunk) = response.chunk().await.map_err(check_timeout)? {
+                    decoder.push(&chunk)?;
+                }
+                decoder.finish()?
+            } else {
+                let url = response.url().clone();
+                let bytes = response.bytes().await.map_err(check_timeout)?;
+                // Let the user see the raw body before anything can fail, as malformed responses
+                // are exactly what one might want to debug.
+                if let Some(response_hook) = &self.response_hook {
+                    response_hook(&RawResponse {
+                        method: &method.into(),
+                        url: &url,
+                        status,
+                        body: &bytes,
+                    });
+                }
+                if let Some(err) = status_error {
+                    // Alpaca servers describe 400/500 errors in a plain text body.
+                    let body = String::from_utf8_lossy(&bytes);
+                    let body = body.trim();
+                    let err = eyre::Report::new(err);
+                    return Err(if body.is_empty() {
+                        err
+                    } else {
+                        err.wrap_err(format!("Server returned {status}: {body}"))
+                    });
+                }
+                let mime_type = content_type
+                    .context("Missing Content-Type header")?
+                    .to_str()?
+                    .parse::<Mime>()?;
+                if let Some((throttle, _interval, key)) = throttle {
+                    throttle.insert(key, mime_type.clone(), bytes.clone());
+                }
+                Resp::from_reqwest(mime_type, &bytes, self.decode_options)?
+            };
 
             tracing::debug!(
                 server_transaction_id = response_transaction.server_transaction_id,
@@ -150,7 +389,7 @@ impl RawClient {
     pub(crate) fn join_url(&self, path: &str) -> eyre::Result<Self> {
         Ok(Self {
             base_url: self.base_url.join(path)?,
-            client_id: self.client_id,
+            ..self.clone()
         })
     }
 }
@@ -159,25 +398,39 @@ impl RawClient {
 #[derive(Debug)]
 pub struct Client {
     inner: RawClient,
+    #[cfg(feature = "camera")]
+    image_bytes_support: Mutex<HashMap<usize, bool>>,
 }
 
 impl Client {
-    /// Create a new client with given server URL.
+    /// Create a new client with given server URL and default settings.
+    ///
+    /// Use [`Self::builder`] to customize the settings.
     pub fn new(base_url: impl IntoUrl) -> eyre::Result<Self> {
-        RawClient::new(base_url.into_url()?).map(|inner| Self { inner })
+        ClientBuilder::new().build(base_url)
     }
 
-    /// Create a new client with given server address.
+    /// Create a new client with given server address and default settings.
     pub fn new_from_addr(addr: impl Into<SocketAddr>) -> Self {
-        Self::new(format!("http://{}/", addr.into()))
-            .expect("creating client from an address should always succeed")
+        ClientBuilder::new().build_from_addr(addr)
+    }
+
+    /// Start building a client with custom settings.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    pub(crate) fn from_raw(inner: RawClient) -> Self {
+        Self {
+            inner,
+            #[cfg(feature = "camera")]
+            image_bytes_support: Mutex::default(),
+        }
     }
 
     /// Get a list of all devices registered on the server.
     pub async fn get_devices(&self) -> eyre::Result<impl Iterator<Item = TypedDevice>> {
-        let api_client = self.inner.join_url("api/v1/")?;
-
-        Ok(self
+        let devices = self
             .inner
             .request::<ValueResponse<Vec<ConfiguredDevice<FallibleDeviceType>>>>(ActionParams {
                 action: "management/v1/configureddevices",
@@ -185,11 +438,57 @@ impl Client {
                 params: (),
             })
             .await?
-            .value
+            .value;
+
+        self.device_clients(devices)
+    }
+
+    /// Recreate device clients from descriptors previously saved via [`Devices::descriptors`](crate::api::Devices::descriptors).
+    ///
+    /// Unlike [`get_devices`](Self::get_devices), this doesn't query the server, so it's up to
+    /// the caller to make sure that descriptors belong to this server. Descriptors with device types
+    /// unsupported by this build are skipped.
+    ///
+    /// If [heartbeat](ClientBuilder::heartbeat) is enabled, this must be called within a Tokio runtime.
+    pub fn devices_from_descriptors(
+        &self,
+        descriptors: impl IntoIterator<Item = DeviceDescriptor>,
+    ) -> eyre::Result<impl Iterator<Item = TypedDevice>> {
+        let devices = descriptors
+            .into_iter()
+            .map(|descriptor| {
+                Ok(ConfiguredDevice {
+                    ty: FallibleDeviceType::deserialize(
+                        IntoDeserializer::<serde::de::value::Error>::into_deserializer(
+                            descriptor.device_type.as_str(),
+                        ),
+                    )?,
+                    number: descriptor.device_number,
+                    name: descriptor.device_name,
+                    unique_id: descriptor.unique_id,
+                })
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        self.device_clients(devices)
+    }
+
+    fn device_clients(
+        &self,
+        devices: Vec<ConfiguredDevice<FallibleDeviceType>>,
+    ) -> eyre::Result<impl Iterator<Item = TypedDevice>> {
+        let api_client = self.inner.join_url("api/v1/")?;
+        let cache_supported_actions = self.inner.cache_supported_actions;
+        let heartbeat = self
+            .inner
+            .heartbeat_interval
+            .map(|interval| (tokio::runtime::Handle::current(), interval));
+
+        Ok(devices
             .into_iter()
             .filter_map(move |device| match device.ty.0 {
-                Ok(device_type) => Some(
-                    Arc::new(RawDeviceClient {
+                Ok(device_type) => {
+                    let device = Arc::new(RawDeviceClient {
                         inner: api_client
                             .join_url(&format!(
                                 "{device_type}/{device_number}/",
@@ -197,11 +496,16 @@ impl Client {
                                 device_number = device.number
                             ))
                             .expect("internal error: failed to join device URL"),
+                        device_type,
                         name: device.name,
                         unique_id: device.unique_id,
-                    })
-                    .into_typed_client(device_type),
-                ),
+                        supported_actions_cache: cache_supported_actions.then(OnceLock::new),
+                    });
+                    if let Some((runtime, interval)) = &heartbeat {
+                        device.spawn_heartbeat(runtime, *interval);
+                    }
+                    Some(device.into_typed_client(device_type))
+                }
                 Err(_) => {
                     tracing::warn!(?device, "Skipping device with unsupported type");
                     None
@@ -220,4 +524,244 @@ impl Client {
             .await
             .map(|value_response| value_response.value)
     }
+
+    /// Get the list of Alpaca API versions supported by the server.
+    ///
+    /// Some servers send a single version number instead of an array, so both are accepted.
+    pub async fn get_api_versions(&self) -> eyre::Result<Vec<u32>> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ApiVersions {
+            Many(Vec<u32>),
+            One(u32),
+        }
+
+        let versions = self
+            .inner
+            .request::<ValueResponse<ApiVersions>>(ActionParams {
+                action: "management/apiversions",
+                method: Method::Get,
+                params: (),
+            })
+            .await?
+            .value;
+
+        Ok(match versions {
+            ApiVersions::Many(versions) => versions,
+            ApiVersions::One(version) => vec![version],
+        })
+    }
+
+    /// Send a GET request to an arbitrary management endpoint, e.g. `v1/description`.
+    ///
+    /// This is an escape hatch for vendor-specific or newer management routes that aren't
+    /// covered by the typed API. The `Value` of the response is deserialized into `T`; use
+    /// [`serde_json::Value`] to get it as-is.
+    pub async fn management_get<T: 'static + DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> eyre::Result<T> {
+        Ok(self
+            .inner
+            .request_at::<ASCOMResult<T>>(&format!("management/{path}"), Method::Get, &())
+            .await??)
+    }
+
+    /// Send a PUT request with the given form parameters to an arbitrary management endpoint.
+    ///
+    /// See [`management_get`](Self::management_get) for details.
+    pub async fn management_put<T: 'static + DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &(impl Serialize + Sync),
+    ) -> eyre::Result<T> {
+        Ok(self
+            .inner
+            .request_at::<ASCOMResult<T>>(&format!("management/{path}"), Method::Put, params)
+            .await??)
+    }
+
+    /// Check whether the server supports the ImageBytes transfer format for the given camera.
+    ///
+    /// This sends an `imagearray` request with `Accept: application/imagebytes` and checks
+    /// whether the server honoured it. When no image is ready, the server only sends back
+    /// a small error response, so this is best called before the first exposure; otherwise
+    /// the full image will be downloaded as part of the check.
+    ///
+    /// The result is cached per camera for the lifetime of the client.
+    #[cfg(feature = "camera")]
+    pub async fn supports_image_bytes(&self, camera_number: usize) -> eyre::Result<bool> {
+        if let Some(&supported) = self
+            .image_bytes_support
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&camera_number)
+        {
+            return Ok(supported);
+        }
+
+        let crate::api::ImageBytesProbe(supported) = self
+            .inner
+            .join_url(&format!("api/v1/camera/{camera_number}/"))?
+            .request(ActionParams {
+                action: "imagearray",
+                method: Method::Get,
+                params: (),
+            })
+            .await?;
+
+        if !supported {
+            tracing::warn!(
+                camera_number,
+                "Server doesn't support ImageBytes, image downloads will use slower JSON format",
+            );
+        }
+
+        let _ = self
+            .image_bytes_support
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(camera_number, supported);
+
+        Ok(supported)
+    }
+}
+
+// The test servers are built with `axum`, which is only available as a dependency of the server.
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::task::JoinHandle;
+
+    /// Serve the router on a random local port until the returned task is aborted.
+    async fn serve(
+        router: axum::Router,
+    ) -> eyre::Result<(String, JoinHandle<std::io::Result<()>>)> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/", listener.local_addr()?);
+        let server_task =
+            tokio::spawn(async move { axum::serve(listener, router.into_make_service()).await });
+        Ok((url, server_task))
+    }
+
+    #[tokio::test]
+    async fn throttled_properties_are_reused() -> eyre::Result<()> {
+        const INTERVAL: Duration = Duration::from_millis(500);
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let handler = {
+            let requests = Arc::clone(&requests);
+            move || async move {
+                let _ = requests.fetch_add(1, Ordering::SeqCst);
+                axum::Json(serde_json::json!({
+                    "Value": 3,
+                    "ErrorNumber": 0,
+                    "ErrorMessage": "",
+                }))
+            }
+        };
+        let router = axum::Router::new()
+            .route(
+                "/api/v1/telescope/0/rightascension",
+                axum::routing::get(handler.clone()),
+            )
+            .route(
+                "/api/v1/telescope/0/declination",
+                axum::routing::get(handler),
+            );
+        let (url, server_task) = serve(router).await?;
+
+        let client = Client::builder()
+            .throttle_property("RightAscension", INTERVAL)
+            .build(url)?
+            .inner
+            .join_url("api/v1/telescope/0/")?;
+        let get = |action| {
+            client.request::<ASCOMResult<i32>>(ActionParams {
+                action,
+                method: Method::Get,
+                params: (),
+            })
+        };
+
+        assert_eq!(get("rightascension").await??, 3);
+        assert_eq!(get("rightascension").await??, 3);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        assert_eq!(get("declination").await??, 3);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(INTERVAL).await;
+        assert_eq!(get("rightascension").await??, 3);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        server_task.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn api_versions_accept_array_and_number() -> eyre::Result<()> {
+        for (value, expected) in [
+            (serde_json::json!([1, 2]), vec![1_u32, 2]),
+            (serde_json::json!(1), vec![1_u32]),
+        ] {
+            let body = serde_json::json!({
+                "Value": value,
+                "ClientTransactionID": 0,
+                "ServerTransactionID": 0,
+            });
+            let router = axum::Router::new().route(
+                "/management/apiversions",
+                axum::routing::get(move || async move { axum::Json(body) }),
+            );
+            let (url, server_task) = serve(router).await?;
+
+            let versions = Client::new(url)?.get_api_versions().await;
+            server_task.abort();
+
+            assert_eq!(versions?, expected);
+        }
+        Ok(())
+    }
+
+    /// Request a property from a server that always fails with 400 and the given body.
+    async fn bad_request(body: &'static str) -> eyre::Report {
+        let router = axum::Router::new().route(
+            "/api/v1/camera/3/name",
+            axum::routing::get(move || async move { (http::StatusCode::BAD_REQUEST, body) }),
+        );
+        let (url, server_task) = serve(router).await.expect("failed to start server");
+
+        let result = Client::new(url)
+            .and_then(|client| client.inner.join_url("api/v1/camera/3/"))
+            .expect("failed to create client")
+            .request::<ASCOMResult<String>>(ActionParams {
+                action: "name",
+                method: Method::Get,
+                params: (),
+            })
+            .await;
+        server_task.abort();
+
+        result.expect_err("request should fail")
+    }
+
+    #[tokio::test]
+    async fn error_status_includes_body() {
+        let err = bad_request("Unknown device number 3").await;
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("Unknown device number 3"),
+            "unexpected error: {message}"
+        );
+        assert!(err.downcast_ref::<reqwest::Error>().is_some());
+    }
+
+    #[tokio::test]
+    async fn error_status_without_body() {
+        let err = bad_request("").await;
+        assert_eq!(err.chain().count(), 1, "unexpected error: {err:#}");
+        assert!(err.downcast_ref::<reqwest::Error>().is_some());
+    }
 }
@@ -1,4 +1,4 @@
-use super::ResponseWithTransaction;
+use super::{Parsing, ResponseWithTransaction};
 use crate::client::ResponseTransaction;
 use crate::response::ValueResponse;
 use crate::{ASCOMError, ASCOMErrorCode, ASCOMResult};
@@ -7,17 +7,62 @@ use serde::de::value::UnitDeserializer;
 use serde::de::DeserializeOwned;
 use std::any::TypeId;
 
+/// Default limit for [`DecodeOptions::max_image_pixels`].
+pub(crate) const DEFAULT_MAX_IMAGE_PIXELS: usize = 256 * 1024 * 1024;
+
+/// Client settings that affect how responses are decoded.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DecodeOptions {
+    pub(crate) parsing: Parsing,
+    /// Maximum total number of pixels (including all planes) accepted in an image response.
+    pub(crate) max_image_pixels: usize,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            parsing: Parsing::default(),
+            max_image_pixels: DEFAULT_MAX_IMAGE_PIXELS,
+        }
+    }
+}
+
+/// Decoder that consumes a response body chunk by chunk as it arrives.
+pub(crate) trait StreamDecoder<T>: Send {
+    fn push(&mut self, chunk: &[u8]) -> eyre::Result<()>;
+
+    fn finish(self: Box<Self>) -> eyre::Result<ResponseWithTransaction<T>>;
+}
+
 pub(crate) trait Response: Sized {
     fn prepare_reqwest(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         request
     }
 
-    fn from_reqwest(mime_type: Mime, bytes: &[u8]) -> eyre::Result<ResponseWithTransaction<Self>>;
+    /// Get a decoder for large bodies that shouldn't be buffered in full before decoding.
+    ///
+    /// Responses that return `None` are decoded via [`from_reqwest`](Self::from_reqwest).
+    fn stream_decoder(
+        _mime_type: &Mime,
+        _options: DecodeOptions,
+    ) -> Option<Box<dyn StreamDecoder<Self>>> {
+        None
+    }
+
+    fn from_reqwest(
+        mime_type: Mime,
+        bytes: &[u8],
+        options: DecodeOptions,
+    ) -> eyre::Result<ResponseWithTransaction<Self>>;
 }
 
 impl ResponseTransaction {
     #[allow(clippy::needless_pass_by_value)]
-    pub(crate) fn from_reqwest(mime_type: Mime, bytes: &[u8]) -> eyre::Result<Self> {
+    pub(crate) fn from_reqwest(
+        mime_type: Mime,
+        bytes: &[u8],
+        parsing: Parsing,
+    ) -> eyre::Result<Self> {
         eyre::ensure!(
             mime_type.essence_str() == mime::APPLICATION_JSON.as_ref(),
             "Expected JSON response, got {}",
@@ -28,14 +73,18 @@ impl ResponseTransaction {
             Some(charset) => eyre::bail!("Unsupported charset {}", charset),
         };
 
-        Ok(serde_json::from_slice(bytes)?)
+        Ok(parsing.from_slice(bytes)?)
     }
 }
 
 impl<T: 'static + DeserializeOwned> Response for ASCOMResult<T> {
-    fn from_reqwest(mime_type: Mime, bytes: &[u8]) -> eyre::Result<ResponseWithTransaction<Self>> {
-        let transaction = ResponseTransaction::from_reqwest(mime_type, bytes)?;
-        let ascom_error = serde_json::from_slice::<ASCOMError>(bytes)?;
+    fn from_reqwest(
+        mime_type: Mime,
+        bytes: &[u8],
+        DecodeOptions { parsing, .. }: DecodeOptions,
+    ) -> eyre::Result<ResponseWithTransaction<Self>> {
+        let transaction = ResponseTransaction::from_reqwest(mime_type, bytes, parsing)?;
+        let ascom_error = parsing.from_slice::<ASCOMError>(bytes)?;
 
         Ok(ResponseWithTransaction {
             transaction,
@@ -44,7 +93,8 @@ impl<T: 'static + DeserializeOwned> Response for ASCOMResult<T> {
                     // Specialization: avoid failure when trying to parse `()` from JSON object with no `Value`.
                     T::deserialize(UnitDeserializer::new())
                 } else {
-                    serde_json::from_slice::<ValueResponse<T>>(bytes)
+                    parsing
+                        .from_slice::<ValueResponse<T>>(bytes)
                         .map(|value_response| value_response.value)
                 }?),
                 _ => Err(ascom_error),
@@ -54,10 +104,14 @@ impl<T: 'static + DeserializeOwned> Response for ASCOMResult<T> {
 }
 
 impl<T: DeserializeOwned> Response for ValueResponse<T> {
-    fn from_reqwest(mime_type: Mime, bytes: &[u8]) -> eyre::Result<ResponseWithTransaction<Self>> {
+    fn from_reqwest(
+        mime_type: Mime,
+        bytes: &[u8],
+        DecodeOptions { parsing, .. }: DecodeOptions,
+    ) -> eyre::Result<ResponseWithTransaction<Self>> {
         Ok(ResponseWithTransaction {
-            transaction: ResponseTransaction::from_reqwest(mime_type, bytes)?,
-            response: serde_json::from_slice(bytes)?,
+            transaction: ResponseTransaction::from_reqwest(mime_type, bytes, parsing)?,
+            response: parsing.from_slice(bytes)?,
         })
     }
 }
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+/// Policy for retrying idempotent `GET` requests that failed due to transient network errors,
+/// such as a reset connection or a timeout.
+///
+/// `PUT` requests are never retried since they change device state. Errors reported by the
+/// device itself are returned as-is as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the initial attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each subsequent one.
+    pub base_delay: Duration,
+    /// Upper bound for the delay between retries.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the given retry, counting from zero.
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2_u32.saturating_pow(retry))
+            .min(self.max_delay)
+    }
+
+    /// Whether the error is a transport-level failure that might go away on its own.
+    pub(crate) fn is_transient(err: &eyre::Report) -> bool {
+        err.downcast_ref::<reqwest::Error>()
+            .is_some_and(|err| err.is_connect() || err.is_request() || err.is_timeout())
+    }
+}
@@ -0,0 +1,59 @@
+use bytes::Bytes;
+use mime::Mime;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+struct CachedResponse {
+    fetched_at: Instant,
+    mime_type: Mime,
+    bytes: Bytes,
+}
+
+/// Client-side cache that limits how often selected properties are actually read from devices.
+#[derive(custom_debug::Debug)]
+pub(crate) struct PropertyThrottle {
+    /// Minimum intervals between reads, keyed by lowercase property name.
+    intervals: HashMap<String, Duration>,
+    #[debug(skip)]
+    responses: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl PropertyThrottle {
+    pub(crate) fn new(intervals: HashMap<String, Duration>) -> Self {
+        Self {
+            intervals,
+            responses: Mutex::default(),
+        }
+    }
+
+    /// Get the minimum read interval configured for the given property, if any.
+    pub(crate) fn interval(&self, property: &str) -> Option<Duration> {
+        self.intervals.get(&property.to_ascii_lowercase()).copied()
+    }
+
+    /// Get a response cached under `key` that is not older than `interval`.
+    pub(crate) fn get(&self, key: &str, interval: Duration) -> Option<(Mime, Bytes)> {
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .filter(|cached| cached.fetched_at.elapsed() < interval)
+            .map(|cached| (cached.mime_type.clone(), cached.bytes.clone()))
+    }
+
+    pub(crate) fn insert(&self, key: String, mime_type: Mime, bytes: Bytes) {
+        let _ = self
+            .responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                key,
+                CachedResponse {
+                    fetched_at: Instant::now(),
+                    mime_type,
+                    bytes,
+                },
+            );
+    }
+}
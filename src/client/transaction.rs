@@ -1,7 +1,9 @@
-use crate::macros::auto_increment;
-use serde::{Deserialize, Serialize};
+use crate::transaction_counter::TransactionCounter;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::num::NonZeroU32;
 
+static CLIENT_TRANSACTION_IDS: TransactionCounter = TransactionCounter::new();
+
 #[derive(Debug, Serialize, Clone, Copy)]
 pub(crate) struct RequestTransaction {
     #[serde(rename = "ClientTransactionID")]
@@ -13,7 +15,7 @@ pub(crate) struct RequestTransaction {
 impl RequestTransaction {
     pub(crate) fn new(client_id: NonZeroU32) -> Self {
         Self {
-            client_transaction_id: auto_increment!(),
+            client_transaction_id: CLIENT_TRANSACTION_IDS.next(),
             client_id,
         }
     }
@@ -27,11 +29,20 @@ pub(crate) struct RequestWithTransaction<T> {
     pub(crate) params: T,
 }
 
+/// Treat 0, which servers send when the ID is unknown, as missing.
+fn deserialize_optional_id<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NonZeroU32>, D::Error> {
+    Ok(Option::<u32>::deserialize(deserializer)?.and_then(NonZeroU32::new))
+}
+
 #[derive(Debug, Clone, Copy, Deserialize)]
 pub(crate) struct ResponseTransaction {
     #[serde(rename = "ClientTransactionID")]
+    #[serde(default, deserialize_with = "deserialize_optional_id")]
     pub(crate) client_transaction_id: Option<NonZeroU32>,
-    #[serde(rename = "ClientID")]
+    #[serde(rename = "ServerTransactionID")]
+    #[serde(default, deserialize_with = "deserialize_optional_id")]
     pub(crate) server_transaction_id: Option<NonZeroU32>,
 }
 
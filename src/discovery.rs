@@ -5,9 +5,30 @@ use serde::{Deserialize, Serialize};
 use std::net::{Ipv6Addr, SocketAddr};
 
 pub(crate) const DISCOVERY_ADDR_V6: Ipv6Addr = ipv6!("ff12::a1:9aca");
-pub(crate) const DISCOVERY_MSG: &[u8] = b"alpacadiscovery1";
 pub(crate) const DEFAULT_DISCOVERY_PORT: u16 = 32227;
 
+/// Discovery requests consist of this prefix followed by the protocol version number.
+const DISCOVERY_MSG_PREFIX: &str = "alpacadiscovery";
+
+/// Discovery protocol versions supported by default.
+pub(crate) const DEFAULT_PROTOCOL_VERSIONS: &[u32] = &[1];
+
+/// Build a discovery request message for the given protocol version.
+#[cfg_attr(not(feature = "client"), allow(dead_code))]
+pub(crate) fn discovery_msg(version: u32) -> String {
+    format!("{DISCOVERY_MSG_PREFIX}{version}")
+}
+
+/// Extract the protocol version from a discovery request message.
+#[cfg_attr(not(feature = "server"), allow(dead_code))]
+pub(crate) fn parse_discovery_msg(msg: &[u8]) -> Option<u32> {
+    let version = msg.strip_prefix(DISCOVERY_MSG_PREFIX.as_bytes())?;
+    if !version.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    std::str::from_utf8(version).ok()?.parse().ok()
+}
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct AlpacaPort {
     #[serde(rename = "AlpacaPort")]
@@ -72,19 +93,26 @@ pub(crate) async fn bind_socket(
 }
 
 #[cfg(feature = "client")]
-pub use crate::client::{BoundDiscoveryClient, DiscoveryClient};
+pub use crate::client::{
+    BoundDiscoveryClient, DiscoveredServer, DiscoveryClient, DiscoveryProtocol,
+};
 #[cfg(feature = "server")]
-pub use crate::server::{BoundDiscoveryServer, DiscoveryServer};
+pub use crate::server::{BoundDiscoveryServer, DiscoveryServer, InterfaceFilter, InterfaceId};
 use netdev::Interface;
 #[cfg(windows)]
 use std::os::windows::prelude::AsRawSocket;
 
 #[cfg(test)]
 mod tests {
-    use super::{DiscoveryClient, DiscoveryServer};
+    use super::{
+        bind_socket, discovery_msg, parse_discovery_msg, AlpacaPort, DiscoveryClient,
+        DiscoveryServer,
+    };
     use futures::StreamExt;
+    use std::convert::Infallible;
     use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
     use std::sync::LazyLock;
+    use std::time::Duration;
 
     const TEST_ALPACA_PORT: u16 = 8378;
 
@@ -196,6 +224,60 @@ mod tests {
         };
     }
 
+    #[test]
+    fn discovery_msg_versions() {
+        assert_eq!(discovery_msg(1), "alpacadiscovery1");
+        assert_eq!(parse_discovery_msg(b"alpacadiscovery1"), Some(1));
+        assert_eq!(parse_discovery_msg(b"alpacadiscovery12"), Some(12));
+        assert_eq!(parse_discovery_msg(b"alpacadiscovery"), None);
+        assert_eq!(parse_discovery_msg(b"alpacadiscovery+1"), None);
+        assert_eq!(parse_discovery_msg(b"somethingelse1"), None);
+    }
+
+    #[tokio::test]
+    async fn discovery_ends_despite_repeated_responses() -> eyre::Result<()> {
+        let responder = bind_socket((Ipv6Addr::UNSPECIFIED, 0)).await?;
+        let response = serde_json::to_vec(&AlpacaPort {
+            alpaca_port: TEST_ALPACA_PORT,
+        })?;
+
+        let client = DiscoveryClient {
+            discovery_port: responder.local_addr()?.port(),
+            timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+
+        tokio::select! {
+            never_returns = async {
+                let mut buf = [0_u8; 64];
+                let (_len, client_addr) = responder.recv_from(&mut buf).await?;
+                // Keep repeating the same response to simulate a misbehaving server.
+                Ok::<Infallible, eyre::Error>(loop {
+                    let _ = responder.send_to(&response, client_addr).await?;
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                })
+            } => match never_returns? {},
+
+            addrs = tokio::time::timeout(Duration::from_secs(5), async {
+                Ok::<_, eyre::Error>(
+                    client
+                        .bind()
+                        .await?
+                        .discover_addrs()
+                        .collect::<Vec<_>>()
+                        .await
+                )
+            }) => {
+                let addrs = addrs??;
+                eyre::ensure!(
+                    addrs.iter().filter(|addr| addr.port() == TEST_ALPACA_PORT).count() <= 1,
+                    "Responses should be deduplicated, got {addrs:#?}"
+                );
+                Ok(())
+            }
+        }
+    }
+
     declare_tests! {
         test_loopback_v4 = Ipv4Addr::LOCALHOST => localhost_v4;
         test_loopback_v6 = Ipv6Addr::LOCALHOST => localhost_v6;
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::ops::RangeInclusive;
+use std::sync::Arc;
 use thiserror::Error;
 
 /// The starting value for error numbers.
@@ -11,7 +12,7 @@ const DRIVER_BASE: u16 = 0x500;
 const MAX: u16 = 0xFFF;
 
 /// Alpaca representation of an ASCOM error code.
-#[derive(Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ASCOMErrorCode(u16);
 
@@ -86,6 +87,34 @@ impl ASCOMErrorCode {
         }
     }
 
+    /// Get the `0`-based driver error code if this is a driver error.
+    ///
+    /// Unlike [`as_driver_error`](Self::as_driver_error), this also checks that the code is
+    /// within the range reserved for drivers, which matters for codes received over the wire.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ascom_alpaca::{ASCOMError, ASCOMErrorCode};
+    ///
+    /// const PORT_ERROR: ASCOMErrorCode = ASCOMErrorCode::new_for_driver(1);
+    ///
+    /// let error = ASCOMError::new(PORT_ERROR, "Port communication error");
+    /// let json = serde_json::to_string(&error).unwrap();
+    /// let error = serde_json::from_str::<ASCOMError>(&json).unwrap();
+    ///
+    /// assert!(error.is_driver_error());
+    /// assert_eq!(error.code.as_driver_code(), Some(1));
+    /// assert_eq!(ASCOMErrorCode::NOT_IMPLEMENTED.as_driver_code(), None);
+    /// ```
+    pub const fn as_driver_code(self) -> Option<u16> {
+        if self.0 >= DRIVER_BASE && self.0 <= MAX {
+            Some(self.0 - DRIVER_BASE)
+        } else {
+            None
+        }
+    }
+
     /// Get the raw error code.
     pub const fn raw(self) -> u16 {
         self.0
@@ -102,6 +131,11 @@ pub struct ASCOMError {
     /// Error message.
     #[serde(rename = "ErrorMessage")]
     pub message: Cow<'static, str>,
+    /// Underlying error, if any.
+    ///
+    /// This is only available locally and is never sent over the wire.
+    #[serde(skip)]
+    source: Option<Arc<dyn std::error::Error + Send + Sync>>,
 }
 
 impl ASCOMError {
@@ -110,6 +144,60 @@ impl ASCOMError {
         Self {
             code,
             message: message.to_string().into(),
+            source: None,
+        }
+    }
+
+    /// Create a new `ASCOMError` from given error code and a static message.
+    ///
+    /// Unlike [`new`](Self::new), this can be used to define errors as constants:
+    ///
+    /// ```
+    /// use ascom_alpaca::{ASCOMError, ASCOMErrorCode};
+    ///
+    /// const ERR_NOT_COOLING: ASCOMError =
+    ///     ASCOMError::new_const(ASCOMErrorCode::new_for_driver(0), "Cooler is not running");
+    /// ```
+    pub const fn new_const(code: ASCOMErrorCode, message: &'static str) -> Self {
+        Self {
+            code,
+            message: Cow::Borrowed(message),
+            source: None,
+        }
+    }
+
+    /// Check whether the error code is in the range reserved for driver-specific errors.
+    pub const fn is_driver_error(&self) -> bool {
+        self.code.as_driver_code().is_some()
+    }
+
+    /// Create a new `ASCOMError` that keeps the underlying error as its [source](std::error::Error::source).
+    ///
+    /// The source is available to local error reporters like `eyre`, but only `code` and `message`
+    /// are sent to Alpaca clients.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ascom_alpaca::{ASCOMError, ASCOMErrorCode};
+    /// use std::error::Error;
+    ///
+    /// let io_error = std::io::Error::new(std::io::ErrorKind::TimedOut, "serial port timed out");
+    /// let error = ASCOMError::with_source(
+    ///     ASCOMErrorCode::new_for_driver(0),
+    ///     "Failed to read position",
+    ///     io_error,
+    /// );
+    /// assert_eq!(error.source().map(|source| source.to_string()).as_deref(), Some("serial port timed out"));
+    /// ```
+    pub fn with_source(
+        code: ASCOMErrorCode,
+        message: impl std::fmt::Display,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            source: Some(Arc::new(source)),
+            ..Self::new(code, message)
         }
     }
 }
@@ -117,6 +205,45 @@ impl ASCOMError {
 /// Result type for ASCOM methods.
 pub type ASCOMResult<T = ()> = Result<T, ASCOMError>;
 
+/// Extension methods for translating and annotating errors in [`ASCOMResult`].
+pub trait ASCOMResultExt<T> {
+    /// Transform the error with `f` if it has the given code, leaving other results unchanged.
+    fn map_err_code(
+        self,
+        code: ASCOMErrorCode,
+        f: impl FnOnce(ASCOMError) -> ASCOMError,
+    ) -> ASCOMResult<T>;
+
+    /// Replace a [`NOT_IMPLEMENTED`](ASCOMErrorCode::NOT_IMPLEMENTED) error with the given default value.
+    fn or_not_implemented(self, default: T) -> ASCOMResult<T>;
+
+    /// Prefix the error message with the given context, keeping the error code.
+    fn context(self, context: impl std::fmt::Display) -> ASCOMResult<T>;
+}
+
+impl<T> ASCOMResultExt<T> for ASCOMResult<T> {
+    fn map_err_code(self, code: ASCOMErrorCode, f: impl FnOnce(ASCOMError) -> ASCOMError) -> Self {
+        match self {
+            Err(err) if err.code == code => Err(f(err)),
+            result => result,
+        }
+    }
+
+    fn or_not_implemented(self, default: T) -> Self {
+        match self {
+            Err(err) if err.code == ASCOMErrorCode::NOT_IMPLEMENTED => Ok(default),
+            result => result,
+        }
+    }
+
+    fn context(self, context: impl std::fmt::Display) -> Self {
+        self.map_err(|err| ASCOMError {
+            message: format!("{context}: {}", err.message).into(),
+            ..err
+        })
+    }
+}
+
 pub(crate) trait ASCOMResultOk {
     type Ok;
 }
@@ -161,6 +288,7 @@ macro_rules! ascom_error_codes {
                 $vis const $name: Self = Self {
                     code: ASCOMErrorCode::$name,
                     message: Cow::Borrowed(ascom_error_codes!(@msg $name $doc)),
+                    source: None,
                 };
             )*
         }
@@ -198,7 +326,7 @@ ascom_error_codes! {
     // Extra codes for internal use only.
 
     /// Reserved 'catch-all' error code (0x4FF) used when nothing else was specified.
-    UNSPECIFIED = 0x4FF,
+    pub(crate) UNSPECIFIED = 0x4FF,
 }
 
 impl ASCOMError {
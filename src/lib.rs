@@ -12,6 +12,7 @@ This crate defines two sets of compilation features that help to keep binary siz
 First set is along the client-server axis:
 
 - `client`: Enables client-side access to Alpaca-capable devices.
+- `tls`: Enables client-side access to servers over HTTPS, including ones using certificates from a private CA.
 - `server`: Allows to expose your own devices as Alpaca servers.
 
 The second set of features is based on the device type and enables the corresponding trait:
@@ -27,6 +28,9 @@ The second set of features is based on the device type and enables the correspon
 - `switch`: Enables [`Switch`](crate::api::Switch).
 - `telescope`: Enables [`Telescope`](crate::api::Telescope).
 
+Additionally, the `fits` feature enables saving camera images as FITS files via [`ImageArray`](crate::api::ImageArray)'s `write_fits` method.
+The `image-interop` feature enables converting them to the `image` crate's [`DynamicImage`](https://docs.rs/image/latest/image/enum.DynamicImage.html) via `to_dynamic_image` for previews.
+
 Once you decided on the features you need, you can add this crate to your `Cargo.toml`. For example, if I'm implementing an Alpaca camera driver, I'd add the following to my `Cargo.toml`:
 
 ```toml
@@ -276,6 +280,28 @@ for camera in devices.iter::<dyn Camera>() {
 
   <img alt="Screenshot of a live view from the simulator camera" src="https://github.com/RReverser/ascom-alpaca-rs/assets/557590/faecb549-dc0c-4f07-902f-7d49429b6458" width="50%" />
 
+### Proxying remote devices
+
+Client devices implement the same traits as local ones, so they can be registered in a server's [`Devices`](crate::api::Devices) to re-expose them, e.g. to build a proxy or aggregate devices from several servers behind a single address:
+
+```no_run
+# #[tokio::main]
+# async fn main() -> eyre::Result<std::convert::Infallible> {
+use ascom_alpaca::{Client, Server};
+
+let remote = Client::new("http://192.168.1.10:11111")?;
+
+let mut server = Server::default();
+// `Devices` can be extended with `TypedDevice`s returned by `get_devices` or discovery.
+server.devices.extend(remote.get_devices().await?);
+// Individual `Arc<dyn Camera>` and other trait objects can be registered directly too.
+
+server.start().await
+# }
+```
+
+Proxied devices keep their original names and unique IDs, but are renumbered within each category in the order they're registered.
+
 ### Logging and tracing
 
 This crate uses [`tracing`](https://crates.io/crates/tracing) framework for logging spans and events, integrating with the Alpaca `ClientID`, `ClientTransactionID` and `ServerTransactionID` fields.
@@ -370,6 +396,7 @@ pub mod discovery;
 mod errors;
 mod params;
 mod response;
+mod transaction_counter;
 
 /// Utilities for testing Alpaca client and server implementations.
 #[cfg(feature = "test")]
@@ -377,10 +404,15 @@ pub mod test_utils;
 
 pub use api::Devices;
 #[cfg(feature = "client")]
-pub use client::Client;
-pub use errors::{ASCOMError, ASCOMErrorCode, ASCOMResult};
+pub use client::{
+    CallMetric, CallOutcome, Client, ClientBuilder, ClientMetrics, RawResponse, RetryPolicy,
+};
+pub use errors::{ASCOMError, ASCOMErrorCode, ASCOMResult, ASCOMResultExt};
 #[cfg(feature = "server")]
-pub use server::{BoundServer, Server};
+pub use server::{
+    Authenticator, BoundServer, DeviceValidationFailure, RateLimits, Server, ServerBuilder,
+    ServerTransactionCounter, TimestampPrecision,
+};
 
 /// Benchmark groups for Criterion.
 ///
@@ -1,18 +1,23 @@
-macro_rules! auto_increment {
-    () => {{
-        static COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(1);
-        std::num::NonZeroU32::new(COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
-            .unwrap()
-    }};
-}
-
-pub(crate) use auto_increment;
-
 #[cfg_attr(
-    not(all(feature = "client", feature = "server", feature = "camera")),
+    not(all(
+        feature = "client",
+        feature = "server",
+        feature = "camera",
+        feature = "telescope"
+    )),
     allow(unused_macro_rules)
 )]
 macro_rules! rpc_trait {
+    // Types with a `via` representation are sent over the wire as that type instead.
+    (@via_ty $ty:ty) => ($ty);
+    (@via_ty $ty:ty, $via:path) => ($via);
+
+    (@to_via $value:ident) => ($value);
+    (@to_via $value:ident, $via:path) => (<$via>::from($value));
+
+    (@from_via $value:ident) => ($value);
+    (@from_via $value:ident, $via:path) => ($value.into());
+
     (@add_extras Device $($rest:tt)*) => {
         rpc_trait!(
             @finish
@@ -57,7 +62,7 @@ macro_rules! rpc_trait {
             }
             {
                 Ok(
-                    $crate::client::REQWEST
+                    self.inner.http
                         .get(self.inner.base_url.join("setup")?)
                         .send()
                         .await?
@@ -65,6 +70,36 @@ macro_rules! rpc_trait {
                         .await?
                 )
             }
+
+            {
+                /// HTML fragment to show under this device on the server's `/setup` page.
+                ///
+                /// The server can implement this to add device-specific configuration links or forms.
+                /// The HTML is embedded as-is, so make sure to escape any untrusted content.
+                ///
+                /// This is not part of the Alpaca API, so clients always return `None`.
+                fn setup_fragment(&self) -> Option<String>
+            }
+            {
+                None
+            }
+            {
+                None
+            }
+
+            {
+                /// Storage for caching [`Self::supported_actions`] in client-side helpers.
+                ///
+                /// Drivers don't need to implement this.
+                #[doc(hidden)]
+                fn supported_actions_cache(&self) -> Option<&std::sync::OnceLock<Vec<String>>>
+            }
+            {
+                None
+            }
+            {
+                self.supported_actions_cache.as_ref()
+            }
         );
     };
     (@add_extras $trait_name:ident $($rest:tt)*) => {
@@ -76,14 +111,27 @@ macro_rules! rpc_trait {
             fn get_storage(storage: &Devices) -> &[std::sync::Arc<Self>] {
                 &storage.$trait_name
             }
+
+            fn get_storage_mut(storage: &mut Devices) -> &mut Vec<std::sync::Arc<Self>> {
+                &mut storage.$trait_name
+            }
         }
 
         impl<T: 'static + $trait_name> $crate::api::devices_impl::RegistrableDevice<dyn $trait_name> for T {
             fn add_to(self, storage: &mut Devices) {
+                $crate::api::devices_impl::check_unique_id(self.unique_id());
                 storage.$trait_name.push(std::sync::Arc::new(self));
             }
         }
 
+        // Trait objects might be remote devices from a client, whose IDs the user doesn't control,
+        // so only local driver types are checked above.
+        impl $crate::api::devices_impl::RegistrableDevice<dyn $trait_name> for std::sync::Arc<dyn $trait_name> {
+            fn add_to(self, storage: &mut Devices) {
+                storage.$trait_name.push(self);
+            }
+        }
+
         #[cfg(test)]
         #[tokio::test]
         #[serial_test::serial($trait_name)]
@@ -137,7 +185,7 @@ macro_rules! rpc_trait {
                 $method_name {
                     $(
                         #[cfg_attr(feature = "client", serde(rename = $param_query))]
-                        $param: $param_ty,
+                        $param: rpc_trait!(@via_ty $param_ty $(, $param_via)?),
                     )*
                 },
             )*
@@ -147,7 +195,7 @@ macro_rules! rpc_trait {
         #[allow(non_camel_case_types, unused_parens)]
         pub(super) enum Response {
             $(
-                $method_name(<$return_type as $crate::errors::ASCOMResultOk>::Ok),
+                $method_name(rpc_trait!(@via_ty <$return_type as $crate::errors::ASCOMResultOk>::Ok $(, $via)?)),
             )*
         }
 
@@ -160,10 +208,7 @@ macro_rules! rpc_trait {
                             #[allow(unused)]
                             let mut params = params;
                             $(
-                                let $param =
-                                    params.extract($param_query)
-                                    $(.map(<$param_via>::into))?
-                                    ?;
+                                let $param = params.extract($param_query)?;
                             )*
 
                             Self::$method_name { $($param),* }
@@ -222,7 +267,7 @@ macro_rules! rpc_trait {
                     $self
                     .exec_action(Action::$method_name {
                         $(
-                            $param,
+                            $param: rpc_trait!(@to_via $param $(, $param_via)?),
                         )*
                     })
                     .await
@@ -252,7 +297,11 @@ macro_rules! rpc_trait {
                     $(
                         Self::$method_name { $($param),* } => {
                             #[allow(deprecated)]
-                            device.$method_name($($param),*).await.map(Response::$method_name)
+                            device
+                                .$method_name($(rpc_trait!(@from_via $param $(, $param_via)?)),*)
+                                .await
+                                $(.map(<$via>::from))?
+                                .map(Response::$method_name)
                         }
                     )*
                 }
@@ -297,7 +346,6 @@ macro_rules! rpc_mod {
             }
         }
 
-        #[cfg(feature = "server")]
         impl TypedDevice {
             pub(crate) fn to_configured_device(&self, as_number: usize) -> $crate::api::ConfiguredDevice<DeviceType> {
                 match *self {
@@ -307,6 +355,63 @@ macro_rules! rpc_mod {
                     )*
                 }
             }
+
+            #[cfg(feature = "server")]
+            pub(crate) fn setup_fragment(&self) -> Option<String> {
+                match *self {
+                    $(
+                        #[cfg(feature = $path)]
+                        Self::$trait_name(ref device) => device.setup_fragment(),
+                    )*
+                }
+            }
+
+            pub(crate) fn unique_id(&self) -> &str {
+                match *self {
+                    $(
+                        #[cfg(feature = $path)]
+                        Self::$trait_name(ref device) => device.unique_id(),
+                    )*
+                }
+            }
+
+            /// Name of the device category, e.g. `Camera`, as used in [`DeviceDescriptor::device_type`](crate::api::DeviceDescriptor::device_type).
+            pub const fn device_type(&self) -> &'static str {
+                match *self {
+                    $(
+                        #[cfg(feature = $path)]
+                        Self::$trait_name(_) => DeviceType::$trait_name.as_str(),
+                    )*
+                }
+            }
+
+            $(
+                #[cfg(feature = $path)]
+                #[doc = concat!("Get the device as a [`", stringify!($trait_name), "`] if it belongs to that category.")]
+                #[allow(irrefutable_let_patterns)]
+                pub fn [<as_ $trait_name:snake>](&self) -> Option<std::sync::Arc<dyn $trait_name>> {
+                    if let Self::$trait_name(device) = self {
+                        Some(std::sync::Arc::clone(device))
+                    } else {
+                        None
+                    }
+                }
+            )*
+        }
+
+        #[cfg(feature = "server")]
+        impl TypedDevice {
+            /// Request a few basic properties that every driver must implement.
+            ///
+            /// Returns the `Connected` state and the `InterfaceVersion`.
+            pub(crate) async fn probe(&self) -> $crate::ASCOMResult<(bool, i32)> {
+                match *self {
+                    $(
+                        #[cfg(feature = $path)]
+                        Self::$trait_name(ref device) => Ok((device.connected().await?, device.interface_version().await?)),
+                    )*
+                }
+            }
         }
 
         #[cfg(feature = "client")]
@@ -455,7 +560,31 @@ macro_rules! rpc_mod {
             }
         }
 
+        /// Visitor for handling devices of different types via [`Devices::accept`].
+        ///
+        /// All methods do nothing by default, so you only need to implement
+        /// the ones for device types you're interested in.
+        #[allow(unused_variables)]
+        pub trait DeviceVisitor {
+            $(
+                #[cfg(feature = $path)]
+                #[doc = concat!("Visit a [`", stringify!($trait_name), "`] with its index within the category.")]
+                fn [<visit_ $trait_name:snake>](&mut self, device: &std::sync::Arc<dyn $trait_name>, index: usize) {}
+            )*
+        }
+
         impl Devices {
+            /// Call the corresponding [`DeviceVisitor`] method for each registered device.
+            #[cfg_attr(not(feature = "__anydevice"), allow(unused_variables))]
+            pub fn accept(&self, visitor: &mut (impl ?Sized + DeviceVisitor)) {
+                $(
+                    #[cfg(feature = $path)]
+                    for (index, device) in self.$trait_name.iter().enumerate() {
+                        visitor.[<visit_ $trait_name:snake>](device, index);
+                    }
+                )*
+            }
+
             /// Iterate over all registered devices.
             ///
             /// The second element of the tuple is the index of the device within its category
@@ -516,3 +645,128 @@ macro_rules! rpc_mod {
 }
 
 pub(crate) use rpc_mod;
+
+/// Create a span at a level that is only known at runtime.
+///
+/// `tracing` requires static metadata for each callsite, so this expands into one callsite per level.
+macro_rules! dynamic_span {
+    ($level:expr, $($args:tt)*) => {{
+        let level: tracing::Level = $level;
+        if level == tracing::Level::ERROR {
+            tracing::error_span!($($args)*)
+        } else if level == tracing::Level::WARN {
+            tracing::warn_span!($($args)*)
+        } else if level == tracing::Level::INFO {
+            tracing::info_span!($($args)*)
+        } else if level == tracing::Level::DEBUG {
+            tracing::debug_span!($($args)*)
+        } else {
+            tracing::trace_span!($($args)*)
+        }
+    }};
+}
+
+pub(crate) use dynamic_span;
+
+/// Operational properties of each device type with a fixed set of them, as listed in the
+/// Platform 7 specification, passed to the given macro.
+///
+/// Both the client-side typed `devicestate` structs and the server-side fallback are
+/// generated from this table so that they can't diverge. Switch properties are indexed
+/// by switch ID and are handled separately.
+///
+/// Types converted via `as` are decoded from the given representation on the client.
+macro_rules! operational_property_table {
+    ($callback:ident) => {
+        $callback! {
+            #[cfg(feature = "camera")]
+            Camera => CameraDeviceState {
+                "CameraState" => camera_state: CameraState,
+                "CCDTemperature" => ccd_temperature: f64,
+                "CoolerPower" => cooler_power: f64,
+                "HeatSinkTemperature" => heat_sink_temperature: f64,
+                "ImageReady" => image_ready: bool,
+                "IsPulseGuiding" => is_pulse_guiding: bool,
+                "PercentCompleted" => percent_completed: i32,
+            }
+
+            #[cfg(feature = "covercalibrator")]
+            CoverCalibrator => CoverCalibratorDeviceState {
+                "Brightness" => brightness: i32,
+                "CalibratorChanging" => calibrator_changing: bool,
+                "CalibratorState" => calibrator_state: CalibratorStatus,
+                "CoverMoving" => cover_moving: bool,
+                "CoverState" => cover_state: CoverStatus,
+            }
+
+            #[cfg(feature = "dome")]
+            Dome => DomeDeviceState {
+                "Altitude" => altitude: f64,
+                "AtHome" => at_home: bool,
+                "AtPark" => at_park: bool,
+                "Azimuth" => azimuth: f64,
+                "ShutterStatus" => shutter_status: ShutterState,
+                "Slewing" => slewing: bool,
+            }
+
+            #[cfg(feature = "filterwheel")]
+            FilterWheel => FilterWheelDeviceState {
+                "Position" => position: i32,
+            }
+
+            #[cfg(feature = "focuser")]
+            Focuser => FocuserDeviceState {
+                "IsMoving" => is_moving: bool,
+                "Position" => position: i32,
+                "Temperature" => temperature: f64,
+            }
+
+            #[cfg(feature = "observingconditions")]
+            ObservingConditions => ObservingConditionsDeviceState {
+                "CloudCover" => cloud_cover: f64,
+                "DewPoint" => dew_point: f64,
+                "Humidity" => humidity: f64,
+                "Pressure" => pressure: f64,
+                "RainRate" => rain_rate: f64,
+                "SkyBrightness" => sky_brightness: f64,
+                "SkyQuality" => sky_quality: f64,
+                "SkyTemperature" => sky_temperature: f64,
+                "StarFWHM" => star_fwhm: f64,
+                "Temperature" => temperature: f64,
+                "WindDirection" => wind_direction: f64,
+                "WindGust" => wind_gust: f64,
+                "WindSpeed" => wind_speed: f64,
+            }
+
+            #[cfg(feature = "rotator")]
+            Rotator => RotatorDeviceState {
+                "IsMoving" => is_moving: bool,
+                "MechanicalPosition" => mechanical_position: f64,
+                "Position" => position: f64,
+            }
+
+            #[cfg(feature = "safetymonitor")]
+            SafetyMonitor => SafetyMonitorDeviceState {
+                "IsSafe" => is_safe: bool,
+            }
+
+            #[cfg(feature = "telescope")]
+            Telescope => TelescopeDeviceState {
+                "Altitude" => altitude: f64,
+                "AtHome" => at_home: bool,
+                "AtPark" => at_park: bool,
+                "Azimuth" => azimuth: f64,
+                "Declination" => declination: f64,
+                "IsPulseGuiding" => is_pulse_guiding: bool,
+                "RightAscension" => right_ascension: f64,
+                "SideOfPier" => side_of_pier: PierSide,
+                "SiderealTime" => sidereal_time: f64,
+                "Slewing" => slewing: bool,
+                "Tracking" => tracking: bool,
+                "UTCDate" => utc_date: SystemTime as TimeRepr<Iso8601>,
+            }
+        }
+    };
+}
+
+pub(crate) use operational_property_table;
@@ -0,0 +1,112 @@
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use http::header::AUTHORIZATION;
+use http::{HeaderMap, StatusCode};
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+
+type CheckFn = dyn Fn(&HeaderMap) -> bool + Send + Sync;
+
+/// Authentication check applied to incoming HTTP requests.
+///
+/// Requests that fail the check are rejected with `401 Unauthorized` before reaching
+/// any device. Note that UDP discovery can't be authenticated, so the server's address
+/// is still advertised to anyone on the network.
+#[derive(Clone)]
+pub struct Authenticator {
+    check: Arc<CheckFn>,
+    protect_setup: bool,
+}
+
+impl Debug for Authenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("protect_setup", &self.protect_setup)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Authenticator {
+    /// Accept requests for which `check` returns `true` given the request headers.
+    pub fn new(check: impl Fn(&HeaderMap) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            check: Arc::new(check),
+            protect_setup: true,
+        }
+    }
+
+    /// Accept requests with an `Authorization: Bearer <token>` header matching the given token.
+    pub fn bearer(token: impl Into<String>) -> Self {
+        let expected = format!("Bearer {}", token.into());
+
+        Self::new(move |headers| {
+            headers
+                .get(AUTHORIZATION)
+                .is_some_and(|value| constant_time_eq(value.as_bytes(), expected.as_bytes()))
+        })
+    }
+
+    /// Set whether the setup pages (both `/setup` and per-device ones) require authentication too.
+    ///
+    /// Defaults to `true`. Setup pages are opened in a browser, which can't easily send
+    /// custom headers, so it can be convenient to exempt them on trusted networks.
+    pub const fn protect_setup(mut self, protect_setup: bool) -> Self {
+        self.protect_setup = protect_setup;
+        self
+    }
+
+    fn is_exempt(&self, path: &str) -> bool {
+        !self.protect_setup
+            && (path == "/setup"
+                || (path.starts_with("/api/")
+                    && path
+                        .rsplit('/')
+                        .next()
+                        .is_some_and(|action| action.eq_ignore_ascii_case("setup"))))
+    }
+
+    pub(crate) async fn check(self, request: Request, next: Next) -> axum::response::Response {
+        if self.is_exempt(request.uri().path()) || (self.check)(request.headers()) {
+            return next.run(request).await;
+        }
+        tracing::warn!(path = request.uri().path(), "Rejected unauthorized request");
+        (StatusCode::UNAUTHORIZED, "Unauthorized").into_response()
+    }
+}
+
+/// Compare secrets without leaking the position of the first mismatch via timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0_u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn bearer_token() {
+        let auth = Authenticator::bearer("secret");
+        let mut headers = HeaderMap::new();
+        assert!(!(auth.check)(&headers));
+
+        let _ = headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer wrong"));
+        assert!(!(auth.check)(&headers));
+
+        let _ = headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        assert!((auth.check)(&headers));
+    }
+
+    #[test]
+    fn setup_exemption() {
+        let auth = Authenticator::bearer("secret");
+        assert!(!auth.is_exempt("/setup"));
+
+        let auth = auth.protect_setup(false);
+        assert!(auth.is_exempt("/setup"));
+        assert!(auth.is_exempt("/api/v1/camera/0/setup"));
+        assert!(!auth.is_exempt("/api/v1/camera/0/connected"));
+        assert!(!auth.is_exempt("/management/v1/configureddevices"));
+    }
+}
@@ -0,0 +1,141 @@
+use super::{Authenticator, InterfaceFilter, Server, TimestampPrecision};
+use crate::api::{RetrieavableDevice, ServerInfo};
+use crate::Devices;
+use std::net::SocketAddr;
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+/// Builder for a [`Server`].
+///
+/// Obtained via [`Server::builder`]. This is an alternative to setting the public fields of
+/// [`Server`] directly; see the corresponding fields for the documentation of each option.
+#[derive(Debug, Default)]
+pub struct ServerBuilder {
+    server: Server,
+}
+
+impl ServerBuilder {
+    /// Create a builder with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the registered devices, replacing any previously registered ones.
+    pub fn devices(mut self, devices: Devices) -> Self {
+        self.server.devices = devices;
+        self
+    }
+
+    /// Set general server information.
+    pub fn info(mut self, info: ServerInfo) -> Self {
+        self.server.info = info;
+        self
+    }
+
+    /// Set the Alpaca API versions the server accepts and advertises.
+    pub fn supported_api_versions(mut self, versions: impl Into<Vec<u32>>) -> Self {
+        self.server.supported_api_versions = versions.into();
+        self
+    }
+
+    /// Limit calls to devices of the given category to at most `max_per_sec` per second.
+    ///
+    /// See [`Server::rate_limits`] for details.
+    #[allow(private_bounds)]
+    pub fn rate_limit<DynTrait: ?Sized + RetrieavableDevice>(
+        mut self,
+        max_per_sec: NonZeroU32,
+    ) -> Self {
+        let _ = self.server.rate_limits.set::<DynTrait>(max_per_sec);
+        self
+    }
+
+    /// Require HTTP requests to pass the given authentication check.
+    pub fn auth(mut self, auth: Authenticator) -> Self {
+        self.server.auth = Some(auth);
+        self
+    }
+
+    /// Set the precision of timestamps in responses.
+    pub const fn timestamp_precision(mut self, precision: TimestampPrecision) -> Self {
+        self.server.timestamp_precision = precision;
+        self
+    }
+
+    /// Set the maximum size of a request body in bytes.
+    pub const fn max_request_body_size(mut self, max_request_body_size: usize) -> Self {
+        self.server.max_request_body_size = max_request_body_size;
+        self
+    }
+
+    /// Set the address for the server to listen on.
+    pub const fn listen_addr(mut self, listen_addr: SocketAddr) -> Self {
+        self.server.listen_addr = listen_addr;
+        self
+    }
+
+    /// Set the port for the discovery server to listen on.
+    pub const fn discovery_port(mut self, discovery_port: u16) -> Self {
+        self.server.discovery_port = discovery_port;
+        self
+    }
+
+    /// Restrict the network interfaces the discovery server responds on.
+    pub const fn discovery_interfaces(mut self, interfaces: InterfaceFilter) -> Self {
+        self.server.discovery_interfaces = interfaces;
+        self
+    }
+
+    /// Set the level of the spans created for each request and for the server loop.
+    pub const fn span_level(mut self, level: tracing::Level) -> Self {
+        self.server.span_level = level;
+        self
+    }
+
+    /// Log requests taking longer than `threshold` as warnings.
+    pub const fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.server.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Cache GET responses for `window` to answer retried requests without re-executing them.
+    pub const fn get_deduplication_window(mut self, window: Duration) -> Self {
+        self.server.get_deduplication_window = Some(window);
+        self
+    }
+
+    /// Set whether concurrent identical GET requests should share a single device call.
+    pub const fn coalesce_reads(mut self, coalesce_reads: bool) -> Self {
+        self.server.coalesce_reads = coalesce_reads;
+        self
+    }
+
+    /// Set whether to return the `ServerTransactionID` in a response header.
+    pub const fn server_transaction_id_header(mut self, enabled: bool) -> Self {
+        self.server.server_transaction_id_header = enabled;
+        self
+    }
+
+    /// Set the maximum number of pending connections in the TCP listen queue.
+    pub const fn listen_backlog(mut self, listen_backlog: i32) -> Self {
+        self.server.listen_backlog = listen_backlog;
+        self
+    }
+
+    /// Set whether to set `TCP_NODELAY` on accepted connections.
+    pub const fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.server.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Set whether to compose `devicestate` responses for drivers that don't implement it.
+    pub const fn synthesize_device_state(mut self, enabled: bool) -> Self {
+        self.server.synthesize_device_state = enabled;
+        self
+    }
+
+    /// Build the server.
+    pub fn build(self) -> Server {
+        self.server
+    }
+}
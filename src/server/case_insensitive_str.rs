@@ -8,6 +8,12 @@ use std::hash::{Hash, Hasher};
 #[repr(transparent)]
 pub(crate) struct CaseInsensitiveStr(str);
 
+impl CaseInsensitiveStr {
+    pub(crate) const fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl AsRef<CaseInsensitiveStr> for str {
     fn as_ref(&self) -> &CaseInsensitiveStr {
         TransparentWrapper::wrap_ref(self)
@@ -0,0 +1,85 @@
+use super::{Error, HttpResult, TimestampPrecision};
+use crate::api::DeviceType;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use http::StatusCode;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Key {
+    pub(crate) device_type: DeviceType,
+    pub(crate) device_number: usize,
+    pub(crate) action: String,
+    pub(crate) params: Vec<(String, String)>,
+}
+
+type SharedRead = Shared<BoxFuture<'static, HttpResult<serde_json::Value>>>;
+
+/// Single-flight registry that lets concurrent identical reads share one device call.
+#[derive(Clone)]
+pub(crate) struct ReadCoalescer {
+    in_flight: Arc<Mutex<BTreeMap<Key, SharedRead>>>,
+    timestamp_precision: TimestampPrecision,
+}
+
+impl ReadCoalescer {
+    pub(crate) fn new(timestamp_precision: TimestampPrecision) -> Self {
+        Self {
+            in_flight: Arc::default(),
+            timestamp_precision,
+        }
+    }
+
+    pub(crate) async fn run<T: 'static + Serialize>(
+        &self,
+        key: Key,
+        read: impl 'static + Send + Future<Output = super::Result<T>>,
+    ) -> HttpResult<serde_json::Value> {
+        let shared = {
+            let mut in_flight = self
+                .in_flight
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            if let Some(shared) = in_flight.get(&key) {
+                tracing::debug!("Joining an identical in-flight request");
+                shared.clone()
+            } else {
+                let this = self.clone();
+                let key_to_remove = key.clone();
+
+                let shared = async move {
+                    let result = Error::into_http_result(read.await).and_then(|ascom_result| {
+                        match ascom_result {
+                            Ok(value) => this
+                                .timestamp_precision
+                                .scope(|| serde_json::to_value(value))
+                                .map(Ok)
+                                .map_err(|err| {
+                                    (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+                                }),
+                            Err(err) => Ok(Err(err)),
+                        }
+                    });
+
+                    let _ = this
+                        .in_flight
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .remove(&key_to_remove);
+
+                    result
+                }
+                .boxed()
+                .shared();
+
+                let _ = in_flight.insert(key, shared.clone());
+                shared
+            }
+        };
+
+        shared.await
+    }
+}
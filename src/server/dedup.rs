@@ -0,0 +1,135 @@
+use axum::body::{Body, Bytes, HttpBody};
+use axum::response::{IntoResponse, Response};
+use http::{HeaderMap, StatusCode};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Largest response body that is kept for deduplication.
+///
+/// Larger responses, like image downloads, are passed through as-is to avoid keeping
+/// whole frames in memory for every transaction within the window.
+const MAX_CACHED_BODY_SIZE: u64 = 256 << 10;
+
+#[derive(PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    pub(crate) client_id: NonZeroU32,
+    pub(crate) client_transaction_id: NonZeroU32,
+    pub(crate) path: String,
+}
+
+struct CachedResponse {
+    created_at: Instant,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl CachedResponse {
+    fn to_response(&self) -> Response {
+        (self.status, self.headers.clone(), self.body.clone()).into_response()
+    }
+}
+
+/// Short-lived cache of GET responses used to answer retried requests without re-executing them.
+#[derive(Clone)]
+pub(crate) struct ResponseCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<Key, CachedResponse>>>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::default(),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &Key) -> Option<Response> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .filter(|cached| cached.created_at.elapsed() < self.ttl)
+            .map(CachedResponse::to_response)
+    }
+
+    pub(crate) async fn insert(&self, key: Key, response: Response) -> Response {
+        if !response
+            .body()
+            .size_hint()
+            .upper()
+            .is_some_and(|size| size <= MAX_CACHED_BODY_SIZE)
+        {
+            return response;
+        }
+
+        let (parts, body) = response.into_parts();
+
+        let body = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(err) => {
+                tracing::error!(%err, "Failed to buffer response for deduplication");
+                return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+            }
+        };
+
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|_, cached| cached.created_at.elapsed() < self.ttl);
+        let _ = entries.insert(
+            key,
+            CachedResponse {
+                created_at: Instant::now(),
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: body.clone(),
+            },
+        );
+        drop(entries);
+
+        Response::from_parts(parts, Body::from(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> Key {
+        Key {
+            client_id: NonZeroU32::MIN,
+            client_transaction_id: NonZeroU32::MIN,
+            path: "/api/v1/camera/0/imagearray".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn large_responses_are_not_cached() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+
+        let _ = cache.insert(key(), "small".into_response()).await;
+        assert!(cache.get(&key()).is_some());
+
+        let large = vec![0_u8; 1 << 20];
+        let response = cache.insert(key(), large.into_response()).await;
+        assert_eq!(
+            axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("body should be readable")
+                .len(),
+            1 << 20
+        );
+        // The previous small response is still cached, but the large one didn't replace it.
+        let cached = cache
+            .get(&key())
+            .expect("small response should remain cached");
+        assert_eq!(
+            axum::body::to_bytes(cached.into_body(), usize::MAX)
+                .await
+                .expect("body should be readable"),
+            "small"
+        );
+    }
+}
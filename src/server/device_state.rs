@@ -0,0 +1,200 @@
+//! Fallback `devicestate` responses composed from individual operational properties.
+
+use super::{format_iso8601, TimestampPrecision};
+#[cfg(feature = "camera")]
+use crate::api::Camera;
+#[cfg(feature = "covercalibrator")]
+use crate::api::CoverCalibrator;
+#[cfg(feature = "dome")]
+use crate::api::Dome;
+#[cfg(feature = "filterwheel")]
+use crate::api::FilterWheel;
+#[cfg(feature = "focuser")]
+use crate::api::Focuser;
+#[cfg(feature = "observingconditions")]
+use crate::api::ObservingConditions;
+#[cfg(feature = "rotator")]
+use crate::api::Rotator;
+#[cfg(feature = "safetymonitor")]
+use crate::api::SafetyMonitor;
+#[cfg(feature = "switch")]
+use crate::api::Switch;
+#[cfg(feature = "telescope")]
+use crate::api::Telescope;
+use crate::api::{Device, DeviceStateItem, DeviceType};
+use crate::{ASCOMErrorCode, ASCOMResult, Devices};
+use serde::{Serialize, Serializer};
+use std::time::SystemTime;
+use time::OffsetDateTime;
+
+tokio::task_local! {
+    /// Precision for timestamps in the response being composed.
+    ///
+    /// Items are serialized to JSON values as soon as they're read, in between device calls,
+    /// so the thread-local [`TimestampPrecision::scope`] can't be used directly.
+    static PRECISION: TimestampPrecision;
+}
+
+/// Timestamp serialized as an ISO 8601 string, as required for `DateTime` operational properties.
+struct Timestamp(SystemTime);
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let precision = PRECISION
+            .try_with(|&precision| precision)
+            .unwrap_or_default();
+        precision
+            .scope(|| format_iso8601(OffsetDateTime::from(self.0)))
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// Convert a property read into a state item, omitting values that aren't known as per the specification.
+fn item<T: Serialize>(name: &str, result: ASCOMResult<T>) -> Option<DeviceStateItem> {
+    let value = match result {
+        Ok(value) => value,
+        Err(err) => {
+            if err.code != ASCOMErrorCode::NOT_IMPLEMENTED {
+                tracing::debug!(name, %err, "Omitting operational property from device state");
+            }
+            return None;
+        }
+    };
+    match serde_json::to_value(value) {
+        Ok(value) => Some(DeviceStateItem {
+            name: name.to_owned(),
+            value,
+        }),
+        Err(err) => {
+            tracing::warn!(name, %err, "Failed to serialize operational property");
+            None
+        }
+    }
+}
+
+/// Operational properties of each device type, as listed in the Platform 7 specification.
+trait OperationalProperties {
+    async fn operational_properties(&self) -> Vec<DeviceStateItem>;
+}
+
+/// Implement [`OperationalProperties`] by concurrently reading the listed properties
+/// and collecting the known ones.
+macro_rules! impl_operational_properties {
+    ($(
+        #[cfg(feature = $feature:literal)]
+        $trait_name:ident => $state_name:ident {
+            $($name:literal => $method:ident: $ty:ty $(as $via:ty)?,)*
+        }
+    )*) => {$(
+        #[cfg(feature = $feature)]
+        impl OperationalProperties for dyn $trait_name {
+            async fn operational_properties(&self) -> Vec<DeviceStateItem> {
+                let ($($method,)*) = futures::join!($(self.$method(),)*);
+                [$(item($name, impl_operational_properties!(@wrap $method $(as $via)?)),)*]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+        }
+    )*};
+
+    (@wrap $value:ident) => {
+        $value
+    };
+
+    // The only converted type is `SystemTime`, which must be sent as an ISO 8601 string.
+    (@wrap $value:ident as $via:ty) => {
+        $value.map(Timestamp)
+    };
+}
+
+crate::macros::operational_property_table!(impl_operational_properties);
+
+#[cfg(feature = "switch")]
+impl OperationalProperties for dyn Switch {
+    async fn operational_properties(&self) -> Vec<DeviceStateItem> {
+        let max_switch = match self.max_switch().await {
+            Ok(max_switch) => max_switch,
+            Err(err) => {
+                tracing::debug!(%err, "Can't enumerate switches for device state");
+                return Vec::new();
+            }
+        };
+        let mut items = Vec::new();
+        for id in 0..max_switch {
+            let (state, value, state_change_complete) = futures::join!(
+                self.get_switch(id),
+                self.get_switch_value(id),
+                self.state_change_complete(id),
+            );
+            items.extend(item(&format!("GetSwitch{id}"), state));
+            items.extend(item(&format!("GetSwitchValue{id}"), value));
+            items.extend(item(
+                &format!("StateChangeComplete{id}"),
+                state_change_complete,
+            ));
+        }
+        items
+    }
+}
+
+/// Return the device's own `devicestate` response, or compose one from its operational
+/// properties with a `TimeStamp` item if the driver doesn't implement it.
+pub(crate) async fn device_state(
+    devices: &Devices,
+    device_type: DeviceType,
+    device_number: usize,
+    precision: TimestampPrecision,
+) -> super::Result<Vec<DeviceStateItem>> {
+    PRECISION
+        .scope(
+            precision,
+            compose_device_state(devices, device_type, device_number),
+        )
+        .await
+}
+
+#[cfg_attr(
+    not(feature = "__anydevice"),
+    allow(unreachable_code, unused_variables)
+)]
+async fn compose_device_state(
+    devices: &Devices,
+    device_type: DeviceType,
+    device_number: usize,
+) -> super::Result<Vec<DeviceStateItem>> {
+    macro_rules! dispatch {
+        ($($feature:literal => $trait_name:ident,)*) => {
+            match device_type {
+                $(
+                    #[cfg(feature = $feature)]
+                    DeviceType::$trait_name => {
+                        let device = devices.get_for_server::<dyn $trait_name>(device_number)?;
+                        match device.device_state().await {
+                            Err(err) if err.code == ASCOMErrorCode::NOT_IMPLEMENTED => {
+                                device.operational_properties().await
+                            }
+                            result => return Ok(result?),
+                        }
+                    }
+                )*
+            }
+        };
+    }
+
+    let mut items: Vec<DeviceStateItem> = dispatch! {
+        "camera" => Camera,
+        "covercalibrator" => CoverCalibrator,
+        "dome" => Dome,
+        "filterwheel" => FilterWheel,
+        "focuser" => Focuser,
+        "observingconditions" => ObservingConditions,
+        "rotator" => Rotator,
+        "safetymonitor" => SafetyMonitor,
+        "switch" => Switch,
+        "telescope" => Telescope,
+    };
+    items.extend(item("TimeStamp", Ok(Timestamp(SystemTime::now()))));
+    Ok(items)
+}
@@ -1,11 +1,67 @@
 use super::DEFAULT_DISCOVERY_PORT;
 use crate::discovery::{
-    bind_socket, get_active_interfaces, AlpacaPort, DISCOVERY_ADDR_V6, DISCOVERY_MSG,
+    bind_socket, get_active_interfaces, parse_discovery_msg, AlpacaPort, DEFAULT_PROTOCOL_VERSIONS,
+    DISCOVERY_ADDR_V6,
 };
 use netdev::Interface;
 use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use tokio::net::UdpSocket;
 
+/// Network interface identified either by its index or by one of its IP addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceId {
+    /// Index of the interface as reported by the operating system.
+    Index(u32),
+    /// Any of the IP addresses assigned to the interface.
+    Addr(IpAddr),
+}
+
+impl InterfaceId {
+    fn matches(self, intf: &Interface) -> bool {
+        match self {
+            Self::Index(index) => intf.index == index,
+            Self::Addr(IpAddr::V4(addr)) => intf.ipv4.iter().any(|net| net.addr == addr),
+            Self::Addr(IpAddr::V6(addr)) => intf.ipv6.iter().any(|net| net.addr == addr),
+        }
+    }
+}
+
+/// Network interfaces the discovery server should respond on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceFilter {
+    /// Respond on all interfaces.
+    #[default]
+    All,
+    /// Respond only on the listed interfaces.
+    Only(&'static [InterfaceId]),
+    /// Respond on all interfaces except the listed ones, e.g. VPN tunnels.
+    Except(&'static [InterfaceId]),
+}
+
+impl InterfaceFilter {
+    fn allows(self, intf: &Interface) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(ids) => ids.iter().any(|id| id.matches(intf)),
+            Self::Except(ids) => !ids.iter().any(|id| id.matches(intf)),
+        }
+    }
+}
+
+/// Check whether the IP address belongs to one of the interface's networks.
+fn is_in_network(ip: IpAddr, intf: &Interface) -> bool {
+    match ip {
+        IpAddr::V4(ip) => intf.ipv4.iter().any(|net| {
+            let mask = u32::from(net.netmask);
+            u32::from(ip) & mask == u32::from(net.addr) & mask
+        }),
+        IpAddr::V6(ip) => intf.ipv6.iter().any(|net| {
+            let mask = u128::from(net.netmask);
+            u128::from(ip) & mask == u128::from(net.addr) & mask
+        }),
+    }
+}
+
 /// Alpaca discovery server configuration.
 #[derive(Debug, Clone, Copy)]
 pub struct Server {
@@ -13,6 +69,16 @@ pub struct Server {
     pub listen_addr: SocketAddr,
     /// Port the Alpaca server is listening on.
     pub alpaca_port: u16,
+    /// Discovery protocol versions to respond to.
+    ///
+    /// Each request is answered regardless of which of these versions the client used.
+    /// Defaults to version 1 only.
+    pub protocol_versions: &'static [u32],
+    /// Network interfaces to respond on.
+    ///
+    /// Excluded interfaces don't join the IPv6 multicast group, and requests coming from
+    /// their networks are ignored. Defaults to [`InterfaceFilter::All`].
+    pub interfaces: InterfaceFilter,
 }
 
 #[tracing::instrument(level = "trace", skip_all, fields(intf.friendly_name = intf.friendly_name.as_ref(), intf.description = intf.description.as_ref(), ?intf.ipv4, ?intf.ipv6))]
@@ -23,22 +89,26 @@ fn join_multicast_group(socket: &UdpSocket, intf: &Interface) {
     }
 }
 
-#[tracing::instrument(level = "debug", ret, skip(socket))]
-fn join_multicast_groups(socket: &UdpSocket, listen_addr: Ipv6Addr) {
+#[tracing::instrument(level = "debug", ret, skip(socket, interfaces))]
+fn join_multicast_groups(socket: &UdpSocket, listen_addr: Ipv6Addr, interfaces: &[Interface]) {
     if listen_addr.is_unspecified() {
         // If it's [::], join multicast on every available interface with IPv6 support.
-        for intf in get_active_interfaces() {
+        for intf in interfaces {
             if !intf.ipv6.is_empty() {
-                join_multicast_group(socket, &intf);
+                join_multicast_group(socket, intf);
             }
         }
     } else {
         // If it's a specific address, find corresponding interface and join multicast on it.
-        let intf = get_active_interfaces()
+        let Some(intf) = interfaces
+            .iter()
             .find(|intf| intf.ipv6.iter().any(|net| net.addr == listen_addr))
-            .expect("internal error: couldn't find the interface of an already bound socket");
+        else {
+            tracing::debug!("Listen address belongs to an excluded interface");
+            return;
+        };
 
-        join_multicast_group(socket, &intf);
+        join_multicast_group(socket, intf);
     }
 }
 
@@ -53,24 +123,38 @@ impl Server {
         Self {
             listen_addr: SocketAddr::new(alpaca_addr.ip(), DEFAULT_DISCOVERY_PORT),
             alpaca_port: alpaca_addr.port(),
+            protocol_versions: DEFAULT_PROTOCOL_VERSIONS,
+            interfaces: InterfaceFilter::All,
         }
     }
 
     /// Binds the discovery server to the specified address and port.
     #[tracing::instrument(level = "error")]
     pub async fn bind(self) -> eyre::Result<BoundServer> {
-        let mut socket = bind_socket(self.listen_addr).await?;
-        if let IpAddr::V6(listen_addr) = self.listen_addr.ip() {
-            // Both netdev::get_interfaces and join_multicast_group can take a long time.
-            // Spawn them all off to the async runtime.
-            socket = tokio::task::spawn_blocking(move || {
-                join_multicast_groups(&socket, listen_addr);
-                socket
-            })
-            .await?;
-        }
+        let socket = bind_socket(self.listen_addr).await?;
+        let (filter, listen_addr) = (self.interfaces, self.listen_addr.ip());
+        // Both netdev::get_interfaces and join_multicast_group can take a long time.
+        // Spawn them all off to the async runtime.
+        let (socket, excluded_interfaces) = tokio::task::spawn_blocking(move || {
+            let (allowed, excluded): (Vec<_>, Vec<_>) =
+                get_active_interfaces().partition(|intf| filter.allows(intf));
+            for intf in &excluded {
+                tracing::debug!(
+                    intf.friendly_name = intf.friendly_name.as_ref(),
+                    intf.index,
+                    "Not responding to discovery on excluded interface"
+                );
+            }
+            if let IpAddr::V6(listen_addr) = listen_addr {
+                join_multicast_groups(&socket, listen_addr, &allowed);
+            }
+            (socket, excluded)
+        })
+        .await?;
         Ok(BoundServer {
             socket,
+            protocol_versions: self.protocol_versions,
+            excluded_interfaces,
             response_msg: serde_json::to_string(&AlpacaPort {
                 alpaca_port: self.alpaca_port,
             })?,
@@ -84,11 +168,35 @@ impl Server {
 #[derive(custom_debug::Debug)]
 pub struct BoundServer {
     socket: UdpSocket,
+    protocol_versions: &'static [u32],
+    #[debug(skip)]
+    excluded_interfaces: Vec<Interface>,
     #[debug(skip)]
     response_msg: String,
 }
 
 impl BoundServer {
+    /// Check whether the request came through one of the excluded interfaces.
+    fn is_excluded(&self, src: SocketAddr) -> bool {
+        match src {
+            // Link-local IPv6 addresses carry the interface they were received on.
+            SocketAddr::V6(src) if src.scope_id() != 0 => self
+                .excluded_interfaces
+                .iter()
+                .any(|intf| intf.index == src.scope_id()),
+            _ => {
+                // We listen on a dual-stack socket, so IPv4 sources arrive as mapped addresses.
+                let ip = match src.ip() {
+                    IpAddr::V6(ip) => ip.to_ipv4_mapped().map_or(IpAddr::V6(ip), IpAddr::V4),
+                    ip @ IpAddr::V4(_) => ip,
+                };
+                self.excluded_interfaces
+                    .iter()
+                    .any(|intf| is_in_network(ip, intf))
+            }
+        }
+    }
+
     /// Get listen address of the discovery server.
     pub fn listen_addr(&self) -> SocketAddr {
         self.socket
@@ -106,20 +214,28 @@ impl BoundServer {
     /// is bound successfully before starting the infinite loop.
     #[tracing::instrument(name = "alpaca_discovery_server_loop", level = "error")]
     pub async fn start(self) -> std::convert::Infallible {
-        let mut buf = [0; DISCOVERY_MSG.len() + 1];
+        // Large enough for any valid request, so that longer packets are detected as unknown.
+        let mut buf = [0; 64];
         loop {
             if let Err(err) = async {
                 let (len, src) = self.socket.recv_from(&mut buf).await?;
-                let data = &buf[..len];
-                if data == DISCOVERY_MSG {
-                    tracing::trace!(%src, "Received Alpaca discovery request");
-                    // UDP packets are sent as whole messages, no need to check length.
-                    let _ = self
-                        .socket
-                        .send_to(self.response_msg.as_bytes(), src)
-                        .await?;
-                } else {
-                    tracing::warn!(%src, "Received unknown packet");
+                if self.is_excluded(src) {
+                    tracing::trace!(%src, "Ignoring request from an excluded interface");
+                    return Ok(());
+                }
+                match parse_discovery_msg(&buf[..len]) {
+                    Some(version) if self.protocol_versions.contains(&version) => {
+                        tracing::trace!(%src, version, "Received Alpaca discovery request");
+                        // UDP packets are sent as whole messages, no need to check length.
+                        let _ = self
+                            .socket
+                            .send_to(self.response_msg.as_bytes(), src)
+                            .await?;
+                    }
+                    Some(version) => {
+                        tracing::debug!(%src, version, "Ignoring unsupported discovery protocol version");
+                    }
+                    None => tracing::warn!(%src, "Received unknown packet"),
                 }
                 Ok::<_, std::io::Error>(())
             }
@@ -1,5 +1,6 @@
 use crate::api::DeviceType;
-use crate::ASCOMError;
+use crate::{ASCOMError, ASCOMResult};
+use http::StatusCode;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -24,3 +25,21 @@ pub(crate) enum Error {
 }
 
 pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+/// Result of a device call after splitting errors into ASCOM errors and HTTP-level failures.
+pub(crate) type HttpResult<T> = std::result::Result<ASCOMResult<T>, (StatusCode, String)>;
+
+impl Error {
+    pub(crate) fn into_http_result<T>(result: Result<T>) -> HttpResult<T> {
+        match result {
+            Ok(response) => Ok(Ok(response)),
+            Err(Self::Ascom(err)) => Ok(Err(err)),
+            Err(err @ (Self::MissingParameter { .. } | Self::BadParameter { .. })) => {
+                Err((StatusCode::BAD_REQUEST, err.to_string()))
+            }
+            Err(err @ (Self::UnknownDeviceIndex { .. } | Self::UnknownAction { .. })) => {
+                Err((StatusCode::NOT_FOUND, err.to_string()))
+            }
+        }
+    }
+}
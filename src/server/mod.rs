@@ -1,8 +1,11 @@
 mod discovery;
-pub use discovery::{BoundServer as BoundDiscoveryServer, Server as DiscoveryServer};
+pub use discovery::{
+    BoundServer as BoundDiscoveryServer, InterfaceFilter, InterfaceId, Server as DiscoveryServer,
+};
 
 mod transaction;
-pub(crate) use transaction::*;
+pub use transaction::ServerTransactionCounter;
+pub(crate) use transaction::{RequestTransaction, ResponseTransaction, ResponseWithTransaction};
 
 mod case_insensitive_str;
 
@@ -10,16 +13,40 @@ mod params;
 pub(crate) use params::ActionParams;
 
 mod response;
+pub(crate) use response::format_iso8601;
+pub use response::TimestampPrecision;
 
 mod error;
-pub(crate) use error::{Error, Result};
+pub(crate) use error::{Error, HttpResult, Result};
+
+mod coalesce;
+use coalesce::ReadCoalescer;
+
+mod dedup;
+use dedup::ResponseCache;
+
+mod auth;
+pub use auth::Authenticator;
+
+mod rate_limit;
+use rate_limit::RateLimiter;
+pub use rate_limit::RateLimits;
+
+mod validate;
+pub use validate::DeviceValidationFailure;
+
+mod builder;
+pub use builder::ServerBuilder;
+
+mod device_state;
 
 #[cfg(feature = "camera")]
 use crate::api::Camera;
 use crate::api::{CargoServerInfo, DevicePath, DeviceType, ServerInfo};
 use crate::discovery::DEFAULT_DISCOVERY_PORT;
+use crate::macros::dynamic_span;
 use crate::response::ValueResponse;
-use crate::Devices;
+use crate::{ASCOMError, Devices};
 use axum::extract::{FromRequest, Path, Request};
 use axum::response::IntoResponse;
 use axum::Router;
@@ -30,6 +57,7 @@ use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::Instrument;
 
 /// The Alpaca server.
@@ -43,6 +71,65 @@ pub struct Server {
     pub listen_addr: SocketAddr,
     /// Port for the discovery server to listen on.
     pub discovery_port: u16,
+    /// Network interfaces for the discovery server to respond on.
+    pub discovery_interfaces: InterfaceFilter,
+    /// If set, requests taking longer than this to handle will be logged as warnings.
+    pub slow_request_threshold: Option<Duration>,
+    /// If set, GET responses are cached for this long and returned as-is when a client
+    /// retries a request with the same `ClientID`, `ClientTransactionID` and path.
+    ///
+    /// This avoids re-reading slow properties from hardware when a client retries after a network error.
+    pub get_deduplication_window: Option<Duration>,
+    /// Whether concurrent identical GET requests to a device should share a single device call.
+    ///
+    /// This is useful when several clients poll the same properties at the same time.
+    pub coalesce_reads: bool,
+    /// Whether to also return the `ServerTransactionID` in an `X-Alpaca-ServerTransactionID` response header.
+    ///
+    /// This is not part of the Alpaca specification, but helps correlating logs at the HTTP layer,
+    /// e.g. in proxies that don't parse response bodies.
+    pub server_transaction_id_header: bool,
+    /// Maximum number of pending connections in the TCP listen queue.
+    pub listen_backlog: i32,
+    /// Whether to set `TCP_NODELAY` on accepted connections.
+    ///
+    /// Disabling Nagle's algorithm noticeably reduces latency for the many small
+    /// request-response pairs typical of Alpaca property polling.
+    pub tcp_nodelay: bool,
+    /// Whether to answer `devicestate` requests for drivers that don't implement [`Device::device_state`](crate::api::Device::device_state).
+    ///
+    /// The response is then composed from the operational properties listed in the Platform 7
+    /// specification for the device type, omitting ones that fail, plus a `TimeStamp` item.
+    pub synthesize_device_state: bool,
+    /// Level of the spans created for each request and for the server loop.
+    ///
+    /// Defaults to [`Level::DEBUG`](tracing::Level::DEBUG) to avoid flooding logs on busy servers.
+    pub span_level: tracing::Level,
+    /// Alpaca API versions advertised via `/management/apiversions`.
+    ///
+    /// Requests to `/api/v{n}/...` routes with any other version are rejected with
+    /// `400 Bad Request`. Defaults to `[1]`.
+    pub supported_api_versions: Vec<u32>,
+    /// Limits on how often device methods can be called.
+    ///
+    /// Calls over the limit are rejected with [`ASCOMErrorCode::INVALID_OPERATION`](crate::ASCOMErrorCode::INVALID_OPERATION)
+    /// instead of waiting, so that a misbehaving client can't starve the hardware link.
+    /// No limits are applied by default.
+    pub rate_limits: RateLimits,
+    /// If set, HTTP requests must pass this check or get rejected with `401 Unauthorized`.
+    ///
+    /// Authentication isn't part of the Alpaca specification and many clients don't support it,
+    /// so it's disabled by default.
+    pub auth: Option<Authenticator>,
+    /// Precision of timestamps in responses, e.g. `Telescope::utc_date`.
+    ///
+    /// Some legacy clients fail to parse more than three fractional digits.
+    pub timestamp_precision: TimestampPrecision,
+    /// Maximum size of a request body in bytes.
+    ///
+    /// Defaults to 16 MiB, which leaves room for large `Parameters` payloads sent to [`Device::action`](crate::api::Device::action).
+    /// Larger requests are rejected with `413 Payload Too Large`.
+    pub max_request_body_size: usize,
 }
 
 impl Default for Server {
@@ -52,6 +139,46 @@ impl Default for Server {
             info: CargoServerInfo!(),
             listen_addr: addr!("[::]:0"),
             discovery_port: DEFAULT_DISCOVERY_PORT,
+            discovery_interfaces: InterfaceFilter::All,
+            slow_request_threshold: None,
+            get_deduplication_window: None,
+            coalesce_reads: false,
+            server_transaction_id_header: false,
+            listen_backlog: 128,
+            tcp_nodelay: true,
+            synthesize_device_state: false,
+            span_level: tracing::Level::DEBUG,
+            supported_api_versions: vec![1],
+            rate_limits: RateLimits::default(),
+            auth: None,
+            timestamp_precision: TimestampPrecision::Full,
+            max_request_body_size: 16 << 20,
+        }
+    }
+}
+
+// Header names are case-insensitive, and `http` requires static ones to be lowercase.
+const SERVER_TRANSACTION_ID_HEADER: http::HeaderName =
+    http::HeaderName::from_static("x-alpaca-servertransactionid");
+
+/// Server options relevant to individual request handlers.
+#[derive(Clone)]
+struct HandlerOptions {
+    slow_request_threshold: Option<Duration>,
+    response_cache: Option<ResponseCache>,
+    server_transaction_id_header: bool,
+    span_level: tracing::Level,
+    timestamp_precision: TimestampPrecision,
+}
+
+impl Default for HandlerOptions {
+    fn default() -> Self {
+        Self {
+            slow_request_threshold: None,
+            response_cache: None,
+            server_transaction_id_header: false,
+            span_level: tracing::Level::DEBUG,
+            timestamp_precision: TimestampPrecision::Full,
         }
     }
 }
@@ -59,6 +186,7 @@ impl Default for Server {
 struct ServerHandler {
     path: String,
     params: ActionParams,
+    options: HandlerOptions,
 }
 
 #[async_trait::async_trait]
@@ -67,8 +195,17 @@ impl<S: Send + Sync> FromRequest<S> for ServerHandler {
 
     async fn from_request(req: Request, state: &S) -> std::result::Result<Self, Self::Rejection> {
         let path = req.uri().path().to_owned();
+        let options = req
+            .extensions()
+            .get::<HandlerOptions>()
+            .cloned()
+            .unwrap_or_default();
         let params = ActionParams::from_request(req, state).await?;
-        Ok(Self { path, params })
+        Ok(Self {
+            path,
+            params,
+            options,
+        })
     }
 }
 
@@ -86,10 +223,43 @@ impl ServerHandler {
                 return (http::StatusCode::BAD_REQUEST, format!("{err:#}")).into_response();
             }
         };
+        let dedup = match (
+            self.options.response_cache.take(),
+            &self.params,
+            request_transaction,
+        ) {
+            (
+                Some(cache),
+                ActionParams::Get(_),
+                RequestTransaction {
+                    client_id: Some(client_id),
+                    client_transaction_id: Some(client_transaction_id),
+                },
+            ) => {
+                let key = dedup::Key {
+                    client_id,
+                    client_transaction_id,
+                    path: self.path.clone(),
+                };
+                if let Some(response) = cache.get(&key) {
+                    tracing::debug!(
+                        path = self.path,
+                        %client_id,
+                        %client_transaction_id,
+                        "Returning cached response for a retried request",
+                    );
+                    return response;
+                }
+                Some((cache, key))
+            }
+            _ => None,
+        };
+
         let response_transaction =
             ResponseTransaction::new(request_transaction.client_transaction_id);
 
-        let span = tracing::error_span!(
+        let span = dynamic_span!(
+            self.options.span_level,
             "handle_alpaca_request",
             path = self.path,
             client_id = request_transaction.client_id,
@@ -97,17 +267,42 @@ impl ServerHandler {
             server_transaction_id = response_transaction.server_transaction_id,
         );
 
-        async move {
+        let server_transaction_id_header = self.options.server_transaction_id_header;
+        let timestamp_precision = self.options.timestamp_precision;
+
+        let response = async move {
             tracing::debug!(params = ?self.params, "Received request");
 
+            let start = Instant::now();
+            let response = make_response(self.params).await;
+            let elapsed = start.elapsed();
+
+            if let Some(threshold) = self.options.slow_request_threshold {
+                if elapsed > threshold {
+                    tracing::warn!(path = self.path, ?elapsed, ?threshold, "Slow request");
+                }
+            }
+
             ResponseWithTransaction {
                 transaction: response_transaction,
-                response: make_response(self.params).await,
+                response,
             }
         }
         .instrument(span)
-        .await
-        .into_response()
+        .await;
+        let mut response = timestamp_precision.scope(|| response.into_response());
+
+        if server_transaction_id_header {
+            let _ = response.headers_mut().insert(
+                SERVER_TRANSACTION_ID_HEADER,
+                response_transaction.server_transaction_id.get().into(),
+            );
+        }
+
+        match dedup {
+            Some((cache, key)) => cache.insert(key, response).await,
+            None => response,
+        }
     }
 }
 
@@ -133,26 +328,72 @@ impl BoundServer {
         self.discovery.listen_addr()
     }
 
+    /// Splits the bound server into the main Alpaca server loop and the discovery server.
+    ///
+    /// This allows to spawn and supervise them independently, e.g. on different runtimes.
+    /// The discovery server can be started via [`BoundDiscoveryServer::start`](crate::discovery::BoundDiscoveryServer::start).
+    ///
+    /// Note: the returned future is an infinite async loop, same as [`Self::start`].
+    pub fn into_parts(
+        self,
+    ) -> (
+        impl Future<Output = eyre::Result<std::convert::Infallible>> + Send + 'static,
+        BoundDiscoveryServer,
+    ) {
+        (self.axum, self.discovery)
+    }
+
     /// Starts the Alpaca and discovery servers.
     ///
     /// Note: this function starts an infinite async loop and it's your responsibility to spawn it off
     /// via [`tokio::spawn`] if necessary.
     pub async fn start(self) -> eyre::Result<std::convert::Infallible> {
+        let (axum, discovery) = self.into_parts();
+
         match tokio::select! {
-            axum = self.axum => axum?,
-            discovery = self.discovery.start() => discovery,
+            axum = axum => axum?,
+            discovery = discovery.start() => discovery,
         } {}
     }
 }
 
+/// Normalizes the action name from the request path.
+///
+/// The Alpaca specification requires URLs to be lowercase, but some clients send
+/// mixed-case action names anyway (e.g. `ImageArray`). Since there's no ambiguity
+/// in action names, we accept those case-insensitively instead of rejecting them.
+///
+/// Device types in the path are still matched strictly and unknown ones result in 404.
+#[cfg_attr(not(feature = "camera"), allow(unused_variables))]
+fn normalize_action(device_type: DeviceType, mut action: String) -> String {
+    action.make_ascii_lowercase();
+
+    // imagearrayvariant is soft-deprecated; we should accept it but
+    // forward to the imagearray handler instead.
+    #[cfg(feature = "camera")]
+    if device_type == DeviceType::Camera && action == "imagearrayvariant" {
+        action.truncate("imagearray".len());
+    }
+
+    action
+}
+
 #[derive(Deserialize)]
 struct ApiPath {
+    api_version: String,
     device_type: DevicePath,
     device_number: usize,
     action: String,
 }
 
 impl Server {
+    /// Start building a server with default settings.
+    ///
+    /// Alternatively, you can construct [`Server`] directly by setting its public fields.
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::new()
+    }
+
     /// Binds the Alpaca and discovery servers to local ports.
     pub async fn bind(self) -> eyre::Result<BoundServer> {
         let addr = self.listen_addr;
@@ -177,7 +418,7 @@ impl Server {
 
         socket.set_nonblocking(true)?;
         socket.bind(&addr.into())?;
-        socket.listen(128)?;
+        socket.listen(self.listen_backlog)?;
 
         let listener = tokio::net::TcpListener::from_std(socket.into())?;
 
@@ -188,12 +429,18 @@ impl Server {
 
         // Bind discovery server only once the Alpaca server is bound successfully.
         // We need to know the bound address & the port to advertise.
-        let discovery_server = DiscoveryServer::for_alpaca_server_at(bound_addr)
-            .bind()
-            .await?;
+        let discovery_server = DiscoveryServer {
+            interfaces: self.discovery_interfaces,
+            ..DiscoveryServer::for_alpaca_server_at(bound_addr)
+        }
+        .bind()
+        .await?;
 
         tracing::debug!("Bound Alpaca discovery server");
 
+        let tcp_nodelay = self.tcp_nodelay;
+        let span_level = self.span_level;
+
         Ok(BoundServer {
             axum: async move {
                 axum::serve(
@@ -202,10 +449,11 @@ impl Server {
                         // .layer(TraceLayer::new_for_http())
                         .into_make_service(),
                 )
+                .tcp_nodelay(tcp_nodelay)
                 .await?;
                 unreachable!("Alpaca server should never stop without an error")
             }
-            .instrument(tracing::error_span!("alpaca_server_loop"))
+            .instrument(dynamic_span!(span_level, "alpaca_server_loop"))
             .boxed(),
             axum_listen_addr: bound_addr,
             discovery: discovery_server,
@@ -223,12 +471,37 @@ impl Server {
     fn into_router(self) -> Router {
         let devices = Arc::new(self.devices);
         let server_info = Arc::new(self.info);
-
-        Router::new()
+        let handler_options = HandlerOptions {
+            slow_request_threshold: self.slow_request_threshold,
+            response_cache: self.get_deduplication_window.map(ResponseCache::new),
+            server_transaction_id_header: self.server_transaction_id_header,
+            span_level: self.span_level,
+            timestamp_precision: self.timestamp_precision,
+        };
+        let read_coalescer = self
+            .coalesce_reads
+            .then(|| ReadCoalescer::new(self.timestamp_precision));
+        let synthesize_device_state = self.synthesize_device_state;
+        let supported_api_versions = Arc::<[u32]>::from(self.supported_api_versions);
+        let rate_limiter = RateLimiter::new(self.rate_limits);
+        let auth = self.auth;
+        let max_request_body_size = self.max_request_body_size;
+
+        // Management responses can get sizable on servers with many devices and are requested
+        // repeatedly by clients aggregating discovery results, so compress them when accepted.
+        let management = Router::new()
             .route(
                 "/management/apiversions",
-                axum::routing::get(|server_handler: ServerHandler| {
-                    server_handler.exec(|_params| async move { ValueResponse { value: [1_u32] } })
+                axum::routing::get({
+                    let supported_api_versions = Arc::clone(&supported_api_versions);
+
+                    move |server_handler: ServerHandler| {
+                        server_handler.exec(|_params| async move {
+                            ValueResponse {
+                                value: Arc::clone(&supported_api_versions),
+                            }
+                        })
+                    }
                 }),
             )
             .route("/management/v1/configureddevices", {
@@ -256,6 +529,10 @@ impl Server {
                     })
                 })
             })
+            .layer(tower_http::compression::CompressionLayer::new());
+
+        let router = Router::new()
+            .merge(management)
             .route("/setup", {
                 let this = Arc::clone(&devices);
                 let server_info = Arc::clone(&server_info);
@@ -265,7 +542,8 @@ impl Server {
                     #[template(path = "setup_template.html")]
                     struct TemplateContext {
                         server_info: Arc<ServerInfo>,
-                        grouped_devices: BTreeMap<DeviceType, Vec<(usize, String)>>,
+                        grouped_devices:
+                            BTreeMap<DeviceType, Vec<(usize, String, Option<String>)>>,
                     }
 
                     let mut ctx = TemplateContext {
@@ -274,12 +552,13 @@ impl Server {
                     };
 
                     for (device, number) in this.iter_all() {
+                        let fragment = device.setup_fragment();
                         let device = device.to_configured_device(number);
 
                         ctx.grouped_devices
                             .entry(device.ty)
                             .or_default()
-                            .push((number, device.name));
+                            .push((number, device.name, fragment));
                     }
 
                     match ctx.render_once() {
@@ -292,43 +571,71 @@ impl Server {
                 })
             })
             .route(
-                "/api/v1/:device_type/:device_number/:action",
+                "/api/:api_version/:device_type/:device_number/:action",
                 axum::routing::any(
                     move |Path(ApiPath {
+                              api_version,
                               device_type: DevicePath(device_type),
                               device_number,
                               action,
                           }),
                           #[cfg(feature = "camera")] headers: http::HeaderMap,
                           server_handler: ServerHandler| async move {
-                        #[cfg(feature = "camera")]
-                        let mut action = action;
-
-                        #[cfg(feature = "camera")]
-                        if device_type == DeviceType::Camera {
-                            // imagearrayvariant is soft-deprecated; we should accept it but
-                            // forward to the imagearray handler instead.
-                            if action == "imagearrayvariant" {
-                                action.truncate("imagearray".len());
-                            }
+                        let is_supported_version = api_version
+                            .strip_prefix('v')
+                            .and_then(|version| version.parse::<u32>().ok())
+                            .is_some_and(|version| supported_api_versions.contains(&version));
+                        if !is_supported_version {
+                            return (
+                                http::StatusCode::BAD_REQUEST,
+                                format!(
+                                    "Unsupported Alpaca API version {api_version:?}, supported versions: {supported_api_versions:?}"
+                                ),
+                            )
+                                .into_response();
+                        }
 
-                            if matches!(server_handler.params, ActionParams::Get { .. })
-                                && action == "imagearray"
-                                && crate::api::ImageArray::is_accepted(&headers)
-                            {
+                        let action = normalize_action(device_type, action);
+
+                        // Setup pages are exempt since they're not ASCOM methods.
+                        if let Some(rate_limiter) =
+                            rate_limiter.as_ref().filter(|_| action != "setup")
+                        {
+                            let key = rate_limit::Key {
+                                device_type,
+                                device_number,
+                                action: action.to_ascii_lowercase(),
+                                is_write: matches!(server_handler.params, ActionParams::Put { .. }),
+                            };
+                            if !rate_limiter.try_acquire(key) {
                                 return server_handler
                                     .exec(|_params| async move {
-                                        Ok::<_, Error>(crate::api::ImageBytesResponse(
-                                            devices
-                                                .get_for_server::<dyn Camera>(device_number)?
-                                                .image_array()
-                                                .await?,
+                                        Err::<(), _>(ASCOMError::invalid_operation(
+                                            "Rate limit exceeded, try again later",
                                         ))
                                     })
                                     .await;
                             }
                         }
 
+                        #[cfg(feature = "camera")]
+                        if device_type == DeviceType::Camera
+                            && matches!(server_handler.params, ActionParams::Get { .. })
+                            && action == "imagearray"
+                            && crate::api::ImageArray::is_accepted(&headers)
+                        {
+                            return server_handler
+                                .exec(|_params| async move {
+                                    Ok::<_, Error>(crate::api::ImageBytesResponse(
+                                        devices
+                                            .get_for_server::<dyn Camera>(device_number)?
+                                            .image_array()
+                                            .await?,
+                                    ))
+                                })
+                                .await;
+                        }
+
                         // Setup endpoint is not an ASCOM method, so doesn't need the transaction and ASCOMResult wrapping.
                         if action == "setup" {
                             let result = devices.get_setup_html(device_type, device_number).await;
@@ -342,6 +649,50 @@ impl Server {
                             return result.into_response();
                         }
 
+                        if synthesize_device_state
+                            && action == "devicestate"
+                            && matches!(server_handler.params, ActionParams::Get { .. })
+                        {
+                            let precision = server_handler.options.timestamp_precision;
+                            return server_handler
+                                .exec(|_params| {
+                                    device_state::device_state(
+                                        &devices,
+                                        device_type,
+                                        device_number,
+                                        precision,
+                                    )
+                                })
+                                .await;
+                        }
+
+                        if let Some(read_coalescer) = read_coalescer
+                            .filter(|_| matches!(server_handler.params, ActionParams::Get { .. }))
+                        {
+                            return server_handler
+                                .exec(|params| async move {
+                                    let key = coalesce::Key {
+                                        device_type,
+                                        device_number,
+                                        action: action.to_ascii_lowercase(),
+                                        params: params.normalized(),
+                                    };
+                                    read_coalescer
+                                        .run(key, async move {
+                                            devices
+                                                .handle_action(
+                                                    device_type,
+                                                    device_number,
+                                                    &action,
+                                                    params,
+                                                )
+                                                .await
+                                        })
+                                        .await
+                                })
+                                .await;
+                        }
+
                         server_handler
                             .exec(|params| {
                                 devices.handle_action(device_type, device_number, &action, params)
@@ -350,5 +701,170 @@ impl Server {
                     },
                 ),
             )
+            .layer(axum::Extension(handler_options))
+            .layer(axum::extract::DefaultBodyLimit::max(max_request_body_size));
+
+        match auth {
+            Some(auth) => router.layer(axum::middleware::from_fn(
+                move |request: Request, next: axum::middleware::Next| {
+                    auth.clone().check(request, next)
+                },
+            )),
+            None => router,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "camera"))]
+mod tests {
+    use super::{normalize_action, Server};
+    use crate::api::{Camera, Device, DeviceType};
+    use crate::ASCOMResult;
+    use async_trait::async_trait;
+    use std::net::SocketAddr;
+    use tokio::task::JoinHandle;
+
+    #[derive(Debug)]
+    struct EchoCamera;
+
+    #[async_trait]
+    impl Device for EchoCamera {
+        fn static_name(&self) -> &str {
+            "Echo camera"
+        }
+
+        fn unique_id(&self) -> &str {
+            "8b3a4e4c-3f5e-4a4e-9d0b-5a2f3c1d6e7f"
+        }
+
+        async fn action(&self, _action: String, parameters: String) -> ASCOMResult<String> {
+            Ok(parameters.len().to_string())
+        }
+    }
+
+    #[async_trait]
+    impl Camera for EchoCamera {}
+
+    /// Serve the server's router on a random local port until the returned task is aborted.
+    async fn serve(server: Server) -> eyre::Result<(SocketAddr, JoinHandle<std::io::Result<()>>)> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let router = server.into_router();
+        let server_task =
+            tokio::spawn(async move { axum::serve(listener, router.into_make_service()).await });
+        Ok((addr, server_task))
+    }
+
+    #[tokio::test]
+    async fn large_action_parameters_are_accepted() -> eyre::Result<()> {
+        const PARAMETERS_SIZE: usize = 5 << 20;
+
+        let mut server = Server::default();
+        server.devices.register(EchoCamera);
+        let (addr, server_task) = serve(server).await?;
+
+        let parameters = "x".repeat(PARAMETERS_SIZE);
+        let response: serde_json::Value = reqwest::Client::new()
+            .put(format!("http://{addr}/api/v1/camera/0/action"))
+            .form(&[("Action", "echo"), ("Parameters", parameters.as_str())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        server_task.abort();
+
+        assert_eq!(response["Value"], PARAMETERS_SIZE.to_string());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn oversized_request_is_payload_too_large() -> eyre::Result<()> {
+        let mut server = Server::default();
+        server.devices.register(EchoCamera);
+        server.max_request_body_size = 1024;
+        let (addr, server_task) = serve(server).await?;
+
+        let parameters = "x".repeat(4096);
+        let response = reqwest::Client::new()
+            .put(format!("http://{addr}/api/v1/camera/0/action"))
+            .form(&[("Action", "echo"), ("Parameters", parameters.as_str())])
+            .send()
+            .await?;
+        server_task.abort();
+
+        assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unsupported_api_versions_are_rejected() -> eyre::Result<()> {
+        let mut server = Server::default();
+        server.devices.register(EchoCamera);
+        let (addr, server_task) = serve(server).await?;
+
+        let get =
+            |version: &str| reqwest::get(format!("http://{addr}/api/{version}/camera/0/name"));
+
+        let response: serde_json::Value = get("v1").await?.error_for_status()?.json().await?;
+        assert_eq!(response["Value"], "Echo camera");
+
+        let response = get("v2").await?;
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+        let body = response.text().await?;
+        assert!(
+            body.contains("Unsupported Alpaca API version \"v2\""),
+            "unexpected body: {body}"
+        );
+
+        assert_eq!(get("vx").await?.status(), http::StatusCode::BAD_REQUEST);
+
+        server_task.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn supported_api_versions_are_configurable() -> eyre::Result<()> {
+        let mut devices = crate::Devices::default();
+        devices.register(EchoCamera);
+        let server = Server::builder()
+            .devices(devices)
+            .supported_api_versions(vec![1, 2])
+            .build();
+        let (addr, server_task) = serve(server).await?;
+
+        let response: serde_json::Value =
+            reqwest::get(format!("http://{addr}/management/apiversions"))
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+        assert_eq!(response["Value"], serde_json::json!([1, 2]));
+
+        let response = reqwest::get(format!("http://{addr}/api/v2/camera/0/name")).await?;
+        assert_eq!(response.status(), http::StatusCode::OK);
+
+        server_task.abort();
+        Ok(())
+    }
+
+    #[test]
+    fn action_case_is_normalized() {
+        for action in ["imagearray", "ImageArray", "IMAGEARRAY"] {
+            assert_eq!(
+                normalize_action(DeviceType::Camera, action.to_owned()),
+                "imagearray"
+            );
+        }
+    }
+
+    #[test]
+    fn image_array_variant_is_forwarded() {
+        for action in ["imagearrayvariant", "ImageArrayVariant"] {
+            assert_eq!(
+                normalize_action(DeviceType::Camera, action.to_owned()),
+                "imagearray"
+            );
+        }
     }
 }
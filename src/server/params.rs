@@ -1,8 +1,11 @@
 use super::case_insensitive_str::CaseInsensitiveStr;
 use super::Error;
+use axum::body::Bytes;
+use axum::extract::rejection::FormRejection;
 use axum::extract::{FromRequest, Request};
 use axum::response::IntoResponse;
 use axum::Form;
+use http::header::CONTENT_TYPE;
 use http::{Method, StatusCode};
 use indexmap::IndexMap;
 use serde::de::{DeserializeOwned, IntoDeserializer};
@@ -69,6 +72,24 @@ where
 }
 
 impl ActionParams {
+    /// Parameters with lowercased GET names in a stable order, for detecting identical requests.
+    pub(crate) fn normalized(&self) -> Vec<(String, String)> {
+        let mut normalized = match self {
+            Self::Get(params) => params
+                .0
+                .iter()
+                .map(|(name, value)| (name.as_str().to_ascii_lowercase(), value.clone()))
+                .collect::<Vec<_>>(),
+            Self::Put(params) => params
+                .0
+                .iter()
+                .map(|(name, value)| (name.clone().into_string(), value.clone()))
+                .collect(),
+        };
+        normalized.sort_unstable();
+        normalized
+    }
+
     pub(crate) fn finish_extraction(self) {
         match self {
             Self::Get(params) => params.finish_extraction(),
@@ -77,6 +98,20 @@ impl ActionParams {
     }
 }
 
+/// Report malformed parameters as 400 Bad Request, as the Alpaca specification requires
+/// for requests the server couldn't interpret.
+///
+/// Other rejections, like oversized or unreadable bodies, keep their own status.
+fn bad_request(rejection: FormRejection) -> axum::response::Response {
+    match rejection {
+        FormRejection::FailedToDeserializeForm(_)
+        | FormRejection::FailedToDeserializeFormBody(_) => {
+            (StatusCode::BAD_REQUEST, rejection.body_text()).into_response()
+        }
+        rejection => rejection.into_response(),
+    }
+}
+
 #[async_trait::async_trait]
 impl<S: Send + Sync> FromRequest<S> for ActionParams {
     type Rejection = axum::response::Response;
@@ -84,18 +119,93 @@ impl<S: Send + Sync> FromRequest<S> for ActionParams {
     async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
         match *req.method() {
             Method::GET => Ok(Self::Get(
-                Form::from_request(req, state)
-                    .await
-                    .map_err(IntoResponse::into_response)?
-                    .0,
+                Form::from_request(req, state).await.map_err(bad_request)?.0,
             )),
-            Method::PUT => Ok(Self::Put(
-                Form::from_request(req, state)
+            Method::PUT if !req.headers().contains_key(CONTENT_TYPE) => {
+                // Clients tend to omit Content-Type when there are no parameters at all.
+                // Treat that as an empty form so that the action reports which parameters are missing.
+                let body = Bytes::from_request(req, state)
                     .await
-                    .map_err(IntoResponse::into_response)?
-                    .0,
+                    .map_err(IntoResponse::into_response)?;
+                if !body.is_empty() {
+                    return Err((
+                        StatusCode::BAD_REQUEST,
+                        "PUT parameters must be sent as application/x-www-form-urlencoded",
+                    )
+                        .into_response());
+                }
+                Ok(Self::Put(OpaqueParams(IndexMap::new())))
+            }
+            Method::PUT => Ok(Self::Put(
+                Form::from_request(req, state).await.map_err(bad_request)?.0,
             )),
             _ => Err((StatusCode::METHOD_NOT_ALLOWED, "Method not allowed").into_response()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    async fn put(content_type: Option<&str>, body: &'static str) -> ActionParams {
+        let mut request = http::Request::put("/");
+        if let Some(content_type) = content_type {
+            request = request.header(CONTENT_TYPE, content_type);
+        }
+        let request = request
+            .body(Body::from(body))
+            .expect("failed to build request");
+        ActionParams::from_request(request, &())
+            .await
+            .expect("failed to extract parameters")
+    }
+
+    fn assert_missing(params: ActionParams, name: &'static str) {
+        let ActionParams::Put(mut params) = params else {
+            panic!("expected PUT parameters");
+        };
+        let err = params
+            .extract::<f64>(name)
+            .expect_err("parameter should be missing");
+        let (status, message) =
+            Error::into_http_result::<()>(Err(err)).expect_err("expected an HTTP error");
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains(name), "{message:?} doesn't mention {name}");
+    }
+
+    #[tokio::test]
+    async fn omitted_put_parameter_is_bad_request() {
+        let params = put(Some("application/x-www-form-urlencoded"), "Light=true").await;
+        assert_missing(params, "Duration");
+    }
+
+    #[tokio::test]
+    async fn put_without_body_reports_missing_parameter() {
+        assert_missing(put(None, "").await, "Duration");
+    }
+
+    #[tokio::test]
+    async fn put_with_unexpected_body_is_bad_request() {
+        let request = http::Request::put("/")
+            .body(Body::from("Duration=1"))
+            .expect("failed to build request");
+        let response = ActionParams::from_request(request, &())
+            .await
+            .expect_err("request without Content-Type should be rejected");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn non_parameter_rejections_keep_their_status() {
+        let request = http::Request::put("/")
+            .header(CONTENT_TYPE, "text/plain")
+            .body(Body::from("Duration=1"))
+            .expect("failed to build request");
+        let response = ActionParams::from_request(request, &())
+            .await
+            .expect_err("request with a non-form Content-Type should be rejected");
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
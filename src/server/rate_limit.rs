@@ -0,0 +1,172 @@
+use crate::api::{DeviceType, RetrieavableDevice};
+use std::collections::BTreeMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Key {
+    pub(crate) device_type: DeviceType,
+    pub(crate) device_number: usize,
+    pub(crate) action: String,
+    pub(crate) is_write: bool,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant, max_per_sec: f64) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = elapsed.mul_add(max_per_sec, self.tokens).min(max_per_sec);
+        self.last_refill = now;
+    }
+}
+
+/// Maximum number of tracked buckets.
+///
+/// Keys come straight from the request URL, so without a cap any client could grow
+/// the map indefinitely by requesting random action names.
+const MAX_BUCKETS: usize = 4096;
+
+/// Per-category limits on how often each device method can be executed.
+///
+/// Limits apply separately to each device, method and HTTP verb, so e.g. polling
+/// `rightascension` on a mount doesn't use up the budget for `declination` or for writes.
+#[derive(Debug, Default, Clone)]
+pub struct RateLimits(BTreeMap<DeviceType, NonZeroU32>);
+
+// we use internal interfaces to get type inference magic to work with polymorphic device types
+#[allow(private_bounds)]
+impl RateLimits {
+    /// Limit calls to devices of the given category to at most `max_per_sec` per second.
+    ///
+    /// Example: `rate_limits.set::<dyn Telescope>(max_per_sec)`.
+    pub fn set<DynTrait: ?Sized + RetrieavableDevice>(
+        &mut self,
+        max_per_sec: NonZeroU32,
+    ) -> &mut Self {
+        let _ = self.0.insert(DynTrait::TYPE, max_per_sec);
+        self
+    }
+}
+
+/// Token buckets enforcing [`RateLimits`].
+#[derive(Clone)]
+pub(crate) struct RateLimiter {
+    limits: Arc<RateLimits>,
+    buckets: Arc<Mutex<BTreeMap<Key, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: RateLimits) -> Option<Self> {
+        if limits.0.is_empty() {
+            return None;
+        }
+        Some(Self {
+            limits: Arc::new(limits),
+            buckets: Arc::default(),
+        })
+    }
+
+    /// Take a token for the given call, returning `false` if the limit has been exceeded.
+    pub(crate) fn try_acquire(&self, key: Key) -> bool {
+        let Some(&max_per_sec) = self.limits.0.get(&key.device_type) else {
+            return true;
+        };
+        let max_per_sec = f64::from(max_per_sec.get());
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(&key) {
+            self.evict(&mut buckets, now);
+        }
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: max_per_sec,
+            last_refill: now,
+        });
+        bucket.refill(now, max_per_sec);
+
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+
+    /// Make room for a new bucket.
+    ///
+    /// Full buckets behave exactly like fresh ones, so they can be dropped without affecting
+    /// the limits. If none are full, the least recently used bucket is dropped instead.
+    fn evict(&self, buckets: &mut BTreeMap<Key, Bucket>, now: Instant) {
+        buckets.retain(|key, bucket| {
+            let Some(&max_per_sec) = self.limits.0.get(&key.device_type) else {
+                return false;
+            };
+            let max_per_sec = f64::from(max_per_sec.get());
+            let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+            elapsed.mul_add(max_per_sec, bucket.tokens) < max_per_sec
+        });
+        if buckets.len() >= MAX_BUCKETS {
+            if let Some(oldest) = buckets
+                .iter()
+                .min_by_key(|(_, bucket)| bucket.last_refill)
+                .map(|(key, _)| key.clone())
+            {
+                let _ = buckets.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "telescope"))]
+mod tests {
+    use super::*;
+    use crate::api::Telescope;
+
+    #[test]
+    fn limits_per_key() {
+        let mut limits = RateLimits::default();
+        let _ = limits.set::<dyn Telescope>(NonZeroU32::new(2).expect("limit should be non-zero"));
+        let limiter =
+            RateLimiter::new(limits).expect("limiter should be created for non-empty limits");
+
+        let key = |action: &str, is_write| Key {
+            device_type: DeviceType::Telescope,
+            device_number: 0,
+            action: action.to_owned(),
+            is_write,
+        };
+
+        assert!(limiter.try_acquire(key("rightascension", false)));
+        assert!(limiter.try_acquire(key("rightascension", false)));
+        assert!(!limiter.try_acquire(key("rightascension", false)));
+        // Other methods and writes have their own buckets.
+        assert!(limiter.try_acquire(key("declination", false)));
+        assert!(limiter.try_acquire(key("rightascension", true)));
+    }
+
+    #[test]
+    fn bucket_count_is_capped() {
+        let mut limits = RateLimits::default();
+        let _ = limits.set::<dyn Telescope>(NonZeroU32::new(2).expect("limit should be non-zero"));
+        let limiter =
+            RateLimiter::new(limits).expect("limiter should be created for non-empty limits");
+
+        for i in 0..MAX_BUCKETS + 10 {
+            assert!(limiter.try_acquire(Key {
+                device_type: DeviceType::Telescope,
+                device_number: 0,
+                action: format!("random{i}"),
+                is_write: false,
+            }));
+        }
+        let buckets = limiter
+            .buckets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        assert!(buckets.len() <= MAX_BUCKETS);
+    }
+}
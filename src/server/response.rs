@@ -1,14 +1,82 @@
-use super::{Error, ResponseWithTransaction};
+use super::{Error, HttpResult, ResponseWithTransaction};
 use crate::response::ValueResponse;
 use crate::{ASCOMError, ASCOMErrorCode, ASCOMResult};
 use axum::response::{IntoResponse, Response};
-use axum::Json;
-use http::StatusCode;
+use http::header::CONTENT_TYPE;
+use http::{HeaderValue, StatusCode};
 use serde::Serialize;
+use std::cell::Cell;
+use std::num::NonZeroU8;
+use time::format_description::well_known::iso8601::{Config, EncodedConfig, TimePrecision};
+use time::format_description::well_known::Iso8601;
+use time::OffsetDateTime;
+
+/// Precision of timestamps sent by the server.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPrecision {
+    /// Three fractional digits, as expected by some legacy clients.
+    Milliseconds,
+    /// Nine fractional digits in ISO 8601 timestamps.
+    ///
+    /// FITS timestamps always have three fractional digits.
+    #[default]
+    Full,
+}
+
+thread_local! {
+    static TIMESTAMP_PRECISION: Cell<TimestampPrecision> = const { Cell::new(TimestampPrecision::Full) };
+}
+
+impl TimestampPrecision {
+    /// Run `f` with timestamps serialized at this precision on the current thread.
+    pub(crate) fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let prev = TIMESTAMP_PRECISION.replace(self);
+        let result = f();
+        TIMESTAMP_PRECISION.set(prev);
+        result
+    }
+}
+
+/// Format a timestamp as ISO 8601 with the precision of the current [`TimestampPrecision::scope`].
+pub(crate) fn format_iso8601(value: OffsetDateTime) -> Result<String, time::error::Format> {
+    const MILLISECONDS: EncodedConfig = Config::DEFAULT
+        .set_time_precision(TimePrecision::Second {
+            decimal_digits: NonZeroU8::new(3),
+        })
+        .encode();
+
+    match TIMESTAMP_PRECISION.get() {
+        TimestampPrecision::Milliseconds => value.format(&Iso8601::<MILLISECONDS>),
+        TimestampPrecision::Full => value.format(&Iso8601::DEFAULT),
+    }
+}
+
+/// Serialize the value into a JSON response.
+///
+/// If serialization fails (e.g. due to a bug in a custom `Serialize` implementation),
+/// the error is logged and returned as a 500 response with a plain-text message,
+/// as expected by Alpaca clients.
+fn json_response(value: &impl Serialize) -> Response {
+    match serde_json::to_vec(value) {
+        Ok(body) => (
+            [(CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+            body,
+        )
+            .into_response(),
+        Err(err) => {
+            tracing::error!(%err, "Failed to serialize response");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to serialize response: {err}"),
+            )
+                .into_response()
+        }
+    }
+}
 
 impl<T: Serialize> IntoResponse for ResponseWithTransaction<ValueResponse<T>> {
     fn into_response(self) -> Response {
-        Json(self).into_response()
+        json_response(&self)
     }
 }
 
@@ -23,7 +91,7 @@ impl<T: Serialize> IntoResponse for ResponseWithTransaction<ASCOMResult<T>> {
             value: Option<T>,
         }
 
-        Json(ResponseWithTransaction {
+        json_response(&ResponseWithTransaction {
             transaction: self.transaction,
             response: match self.response {
                 Ok(value) => Repr {
@@ -40,7 +108,20 @@ impl<T: Serialize> IntoResponse for ResponseWithTransaction<ASCOMResult<T>> {
                 }
             },
         })
-        .into_response()
+    }
+}
+
+impl<T> IntoResponse for ResponseWithTransaction<HttpResult<T>>
+where
+    ResponseWithTransaction<ASCOMResult<T>>: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        self.response
+            .map(|ascom_result| ResponseWithTransaction {
+                transaction: self.transaction,
+                response: ascom_result,
+            })
+            .into_response()
     }
 }
 
@@ -49,20 +130,59 @@ where
     ResponseWithTransaction<ASCOMResult<T>>: IntoResponse,
 {
     fn into_response(self) -> Response {
-        match self.response {
-            Ok(response) => Ok(Ok(response)),
-            Err(Error::Ascom(err)) => Ok(Err(err)),
-            Err(err @ (Error::MissingParameter { .. } | Error::BadParameter { .. })) => {
-                Err((StatusCode::BAD_REQUEST, err.to_string()))
-            }
-            Err(err @ (Error::UnknownDeviceIndex { .. } | Error::UnknownAction { .. })) => {
-                Err((StatusCode::NOT_FOUND, err.to_string()))
-            }
-        }
-        .map(|ascom_result| ResponseWithTransaction {
+        ResponseWithTransaction {
             transaction: self.transaction,
-            response: ascom_result,
-        })
+            response: Error::into_http_result(self.response),
+        }
         .into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::ResponseTransaction;
+    use serde::ser::{Error as _, Serializer};
+
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            Err(S::Error::custom("broken driver value"))
+        }
+    }
+
+    #[test]
+    fn iso8601_precision() {
+        let value = OffsetDateTime::UNIX_EPOCH + std::time::Duration::from_millis(1_500);
+        let format = |precision: TimestampPrecision| {
+            precision
+                .scope(|| format_iso8601(value))
+                .expect("timestamp should be formattable")
+        };
+
+        assert_eq!(
+            format(TimestampPrecision::Full),
+            "1970-01-01T00:00:01.500000000Z"
+        );
+        assert_eq!(
+            format(TimestampPrecision::Milliseconds),
+            "1970-01-01T00:00:01.500Z"
+        );
+    }
+
+    #[test]
+    fn serialization_failure_is_internal_server_error() {
+        let response = ResponseWithTransaction {
+            transaction: ResponseTransaction::new(None),
+            response: Ok::<_, ASCOMError>(Unserializable),
+        }
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body =
+            futures::executor::block_on(axum::body::to_bytes(response.into_body(), usize::MAX))
+                .expect("failed to read response body");
+        assert!(String::from_utf8_lossy(&body).contains("broken driver value"));
+    }
+}
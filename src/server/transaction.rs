@@ -1,23 +1,51 @@
 use super::ActionParams;
-use crate::macros::auto_increment;
-use serde::Serialize;
+use crate::transaction_counter::TransactionCounter;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
 use std::num::NonZeroU32;
 
-#[derive(Debug, Serialize, Clone, Copy)]
+static SERVER_TRANSACTION_IDS: TransactionCounter = TransactionCounter::new();
+
+/// Process-wide source of `ServerTransactionID`s assigned to server responses.
+///
+/// IDs are shared by all servers in the process. They increase with every response
+/// and wrap around to 1 after `u32::MAX`, so they're never 0.
+#[derive(Debug, Clone, Copy)]
+pub struct ServerTransactionCounter;
+
+impl ServerTransactionCounter {
+    /// Get the ID assigned to the most recent response, or `None` if no responses were sent yet.
+    ///
+    /// This is mainly useful in tests to check that IDs increase across requests.
+    pub fn last() -> Option<NonZeroU32> {
+        SERVER_TRANSACTION_IDS.last()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub(crate) struct ResponseTransaction {
-    #[serde(rename = "ClientTransactionID")]
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) client_transaction_id: Option<NonZeroU32>,
-
-    #[serde(rename = "ServerTransactionID")]
     pub(crate) server_transaction_id: NonZeroU32,
 }
 
+impl Serialize for ResponseTransaction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut transaction = serializer.serialize_struct("ResponseTransaction", 2)?;
+        // The specification requires the field to be always present, with 0 if the client didn't send one.
+        transaction.serialize_field(
+            "ClientTransactionID",
+            &self.client_transaction_id.map_or(0, NonZeroU32::get),
+        )?;
+        transaction.serialize_field("ServerTransactionID", &self.server_transaction_id)?;
+        transaction.end()
+    }
+}
+
 impl ResponseTransaction {
     pub(crate) fn new(client_transaction_id: Option<NonZeroU32>) -> Self {
         Self {
             client_transaction_id,
-            server_transaction_id: auto_increment!(),
+            server_transaction_id: SERVER_TRANSACTION_IDS.next(),
         }
     }
 }
@@ -52,3 +80,25 @@ impl RequestTransaction {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_client_transaction_id_is_serialized_as_zero() {
+        let json = serde_json::to_value(ResponseTransaction::new(None))
+            .expect("failed to serialize transaction");
+        assert_eq!(json["ClientTransactionID"], 0_u32);
+        assert!(json["ServerTransactionID"]
+            .as_u64()
+            .is_some_and(|id| id > 0));
+    }
+
+    #[test]
+    fn client_transaction_id_is_echoed() {
+        let json = serde_json::to_value(ResponseTransaction::new(NonZeroU32::new(42)))
+            .expect("failed to serialize transaction");
+        assert_eq!(json["ClientTransactionID"], 42_u32);
+    }
+}
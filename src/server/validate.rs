@@ -0,0 +1,146 @@
+use super::Server;
+#[cfg(feature = "camera")]
+use crate::api::Camera;
+use crate::ASCOMError;
+#[cfg(feature = "camera")]
+use crate::{ASCOMErrorCode, ASCOMResult};
+
+/// Device that failed the startup check in [`Server::validate_devices`].
+#[derive(Debug, Clone)]
+pub struct DeviceValidationFailure {
+    /// Device category, e.g. `Camera`.
+    pub device_type: String,
+    /// Index of the device within its category.
+    pub device_number: usize,
+    /// Name of the device.
+    pub name: String,
+    /// Error returned by the device.
+    pub error: ASCOMError,
+}
+
+impl Server {
+    /// Probe each registered device by reading its `Connected` and `InterfaceVersion` properties.
+    ///
+    /// For connected cameras, this also checks that related properties are consistent with
+    /// each other, e.g. that `FastReadout` is implemented when `CanFastReadout` is `true`.
+    /// Only getters are called, so the device state is left untouched.
+    ///
+    /// This is meant to be called before [`bind`](Self::bind) to surface misconfigured drivers
+    /// at startup rather than on the first client request. Results are logged, and each
+    /// problem found is collected into the returned list.
+    pub async fn validate_devices(&self) -> Vec<DeviceValidationFailure> {
+        let mut failures = Vec::new();
+
+        for (device, number) in self.devices.iter_all() {
+            let configured = device.to_configured_device(number);
+            let mut errors = Vec::new();
+
+            match device.probe().await {
+                Ok((connected, interface_version)) => {
+                    tracing::debug!(
+                        device_type = %configured.ty,
+                        device_number = number,
+                        name = configured.name,
+                        connected,
+                        interface_version,
+                        "Device responded to probe",
+                    );
+
+                    #[cfg(feature = "camera")]
+                    if let (true, Some(camera)) = (connected, device.as_camera()) {
+                        errors = camera_invariant_violations(&*camera).await;
+                    }
+                }
+                Err(error) => errors.push(error),
+            }
+
+            for error in errors {
+                tracing::warn!(
+                    device_type = %configured.ty,
+                    device_number = number,
+                    name = configured.name,
+                    %error,
+                    "Device failed validation",
+                );
+                failures.push(DeviceValidationFailure {
+                    device_type: configured.ty.to_string(),
+                    device_number: number,
+                    name: configured.name.clone(),
+                    error,
+                });
+            }
+        }
+
+        failures
+    }
+}
+
+#[cfg(feature = "camera")]
+fn implemented<T>(result: ASCOMResult<T>) -> ASCOMResult<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(err) if err.code == ASCOMErrorCode::NOT_IMPLEMENTED => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Check cross-property invariants that ConformU would otherwise only catch in a full run.
+#[cfg(feature = "camera")]
+async fn camera_invariant_violations(camera: &dyn Camera) -> Vec<ASCOMError> {
+    let mut violations = Vec::new();
+    if let Err(error) = check_camera(camera, &mut violations).await {
+        violations.push(error);
+    }
+    violations
+}
+
+#[cfg(feature = "camera")]
+async fn check_camera(camera: &dyn Camera, violations: &mut Vec<ASCOMError>) -> ASCOMResult {
+    let mut require = |condition: bool, message: &str| {
+        if !condition {
+            violations.push(ASCOMError::new(ASCOMErrorCode::NOT_IMPLEMENTED, message));
+        }
+    };
+
+    if camera.can_fast_readout().await? {
+        require(
+            implemented(camera.fast_readout().await)?,
+            "CanFastReadout is true but FastReadout is not implemented",
+        );
+    }
+
+    if implemented(camera.readout_modes().await)? {
+        require(
+            implemented(camera.readout_mode().await)?,
+            "ReadoutModes is implemented but ReadoutMode is not",
+        );
+    }
+
+    if camera.can_set_ccd_temperature().await? {
+        require(
+            implemented(camera.set_ccd_temperature().await)?,
+            "CanSetCCDTemperature is true but SetCCDTemperature is not implemented",
+        );
+    }
+
+    let (max_bin_x, max_bin_y) = (camera.max_bin_x().await?, camera.max_bin_y().await?);
+    if max_bin_x > 1 {
+        require(
+            implemented(camera.bin_x().await)?,
+            "MaxBinX is greater than 1 but BinX is not implemented",
+        );
+    }
+    if max_bin_y > 1 {
+        require(
+            implemented(camera.bin_y().await)?,
+            "MaxBinY is greater than 1 but BinY is not implemented",
+        );
+    }
+    if max_bin_x != max_bin_y && !camera.can_asymmetric_bin().await? {
+        violations.push(ASCOMError::invalid_value(format_args!(
+            "MaxBinX ({max_bin_x}) differs from MaxBinY ({max_bin_y}) but CanAsymmetricBin is false"
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Source of unique, increasing transaction IDs.
+///
+/// After `u32::MAX`, IDs wrap around to 1, as 0 is reserved for missing IDs.
+#[derive(Debug)]
+pub(crate) struct TransactionCounter(AtomicU32);
+
+impl TransactionCounter {
+    pub(crate) const fn new() -> Self {
+        Self(AtomicU32::new(1))
+    }
+
+    /// Allocate the next ID.
+    pub(crate) fn next(&self) -> NonZeroU32 {
+        loop {
+            if let Some(id) = NonZeroU32::new(self.0.fetch_add(1, Ordering::Relaxed)) {
+                return id;
+            }
+        }
+    }
+
+    /// Get the most recently allocated ID, if any.
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    pub(crate) fn last(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(self.0.load(Ordering::Relaxed).wrapping_sub(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_around_to_one() {
+        let counter = TransactionCounter(AtomicU32::new(u32::MAX - 1));
+        let ids: Vec<u32> = std::iter::repeat_with(|| counter.next().get())
+            .take(4)
+            .collect();
+        assert_eq!(ids, [u32::MAX - 1, u32::MAX, 1, 2]);
+        assert_eq!(counter.last(), NonZeroU32::new(2));
+    }
+
+    #[test]
+    fn last_is_none_before_first_id() {
+        let counter = TransactionCounter::new();
+        assert_eq!(counter.last(), None);
+        let first = counter.next();
+        assert_eq!(counter.last(), Some(first));
+    }
+}